@@ -0,0 +1,106 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, GenericParam, Ident};
+
+/// Derive `TagEncode<T>` for a fieldless enum, for every `T`.
+///
+/// The enum's discriminants are used as the tag values. Whether the largest
+/// discriminant fits within the alignment bits of `T` is checked at compile
+/// time whenever the encoding is used with a particular `T`.
+#[proc_macro_derive(PointerTag)]
+pub fn derive_pointer_tag(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match pointer_tag(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+fn pointer_tag(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`PointerTag` can only be derived for enums",
+            ))
+        }
+    };
+
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`PointerTag` cannot be derived for empty enums",
+        ));
+    }
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "`PointerTag` can only be derived for fieldless enums",
+            ));
+        }
+    }
+
+    let variants: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+
+    let target = Ident::new("__ChePointee", Span::call_site());
+
+    let mut generics = input.generics.clone();
+    generics
+        .params
+        .push(GenericParam::Type(target.clone().into()));
+
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let invalid = format!("invalid tag for `{name}`");
+
+    Ok(quote! {
+        impl #impl_generics ::chering::tag_ptr::TagEncode<#target> for #name #ty_generics #where_clause {
+            const MAX: usize = {
+                let discriminants = [#(#name::#variants as usize),*];
+
+                let mut max = 0;
+                let mut i = 0;
+
+                while i < discriminants.len() {
+                    if discriminants[i] > max {
+                        max = discriminants[i];
+                    }
+
+                    i += 1;
+                }
+
+                max
+            };
+
+            #[inline]
+            fn encode(self) -> ::chering::tag_ptr::Tag<#target> {
+                let () = <Self as ::chering::tag_ptr::TagEncode<#target>>::FITS;
+
+                // SAFETY: `FITS` ensures that every discriminant fits within the alignment bits.
+                unsafe { ::chering::tag_ptr::Tag::new_unchecked(self as usize) }
+            }
+
+            #[inline]
+            #[track_caller]
+            fn decode(tag: ::chering::tag_ptr::Tag<#target>) -> Self {
+                let tag = tag.get();
+
+                #(
+                    if tag == #name::#variants as usize {
+                        return #name::#variants;
+                    }
+                )*
+
+                ::core::panic!(#invalid)
+            }
+        }
+    })
+}
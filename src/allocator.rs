@@ -0,0 +1,203 @@
+//! The allocator API used by the containers.
+//!
+//! With the `allocator-api2` feature enabled this is `allocator_api2`'s `Allocator`
+//! trait, which lets containers allocate from arenas, bump allocators, instrumented
+//! allocators and so on, on stable. Otherwise it is a sealed stand-in with the same
+//! shape, which is only implemented for the global allocator.
+
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator-api2"))]
+pub use self::global::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator-api2"))]
+mod global {
+    use alloc::alloc::{alloc, alloc_zeroed, dealloc, realloc};
+    use core::{alloc::Layout, fmt, ptr::NonNull};
+
+    use crate::strict;
+
+    /// The error returned when an allocator fails to allocate memory.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct AllocError;
+
+    impl fmt::Display for AllocError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("memory allocation failed")
+        }
+    }
+
+    impl core::error::Error for AllocError {}
+
+    /// The global memory allocator, which is whatever `#[global_allocator]` is.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Global;
+
+    mod private {
+        pub trait Sealed {}
+    }
+
+    impl private::Sealed for Global {}
+
+    /// An allocator that containers can allocate from.
+    ///
+    /// This mirrors the unstable `core::alloc::Allocator` trait, but can only be
+    /// implemented for other allocators with the `allocator-api2` feature enabled.
+    ///
+    /// # Safety
+    ///
+    /// - Memory returned by an allocator must stay valid until it is deallocated, even
+    ///   if the allocator is moved or cloned, and a clone must behave like the same
+    ///   allocator.
+    pub unsafe trait Allocator: private::Sealed {
+        /// Allocate memory that fits `layout`.
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError>;
+
+        /// Allocate memory that fits `layout` and is filled with zero bytes.
+        #[inline]
+        fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = self.allocate(layout)?;
+
+            // SAFETY: The allocation is valid for `ptr.len()` bytes.
+            unsafe { ptr.cast::<u8>().write_bytes(0, ptr.len()) };
+
+            Ok(ptr)
+        }
+
+        /// Deallocate memory that was allocated with `layout`.
+        ///
+        /// # Safety
+        ///
+        /// - The caller must ensure that `ptr` was allocated by this allocator with
+        ///   `layout`, and that it is not used again.
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+        /// Grow memory that was allocated with `old_layout` to fit `new_layout`, keeping
+        /// its contents.
+        ///
+        /// # Safety
+        ///
+        /// - The caller must ensure that `ptr` was allocated by this allocator with
+        ///   `old_layout`, and that `new_layout` has the same alignment and is at least
+        ///   as large. On success `ptr` must not be used again.
+        unsafe fn grow(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError>;
+
+        /// Shrink memory that was allocated with `old_layout` to fit `new_layout`,
+        /// keeping as much of its contents as fits.
+        ///
+        /// # Safety
+        ///
+        /// - The caller must ensure that `ptr` was allocated by this allocator with
+        ///   `old_layout`, and that `new_layout` has the same alignment and is at most
+        ///   as large. On success `ptr` must not be used again.
+        unsafe fn shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError>;
+    }
+
+    // SAFETY: The global allocator upholds the same requirements.
+    unsafe impl Allocator for Global {
+        #[inline]
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = if layout.size() == 0 {
+                // SAFETY: Alignments are never zero.
+                unsafe { NonNull::new_unchecked(strict::without_provenance_mut(layout.align())) }
+            } else {
+                // SAFETY: The layout is not zero-sized.
+                NonNull::new(unsafe { alloc(layout) }).ok_or(AllocError)?
+            };
+
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        #[inline]
+        fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = if layout.size() == 0 {
+                // SAFETY: Alignments are never zero.
+                unsafe { NonNull::new_unchecked(strict::without_provenance_mut(layout.align())) }
+            } else {
+                // SAFETY: The layout is not zero-sized.
+                NonNull::new(unsafe { alloc_zeroed(layout) }).ok_or(AllocError)?
+            };
+
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        #[inline]
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if layout.size() != 0 {
+                // SAFETY: The caller ensures that this was allocated with `layout`.
+                unsafe { dealloc(ptr.as_ptr(), layout) };
+            }
+        }
+
+        #[inline]
+        unsafe fn grow(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            // SAFETY: The caller ensures that this is valid.
+            unsafe { self.reallocate(ptr, old_layout, new_layout) }
+        }
+
+        #[inline]
+        unsafe fn shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            // SAFETY: The caller ensures that this is valid.
+            unsafe { self.reallocate(ptr, old_layout, new_layout) }
+        }
+    }
+
+    impl Global {
+        /// Grow or shrink memory, handling zero-sized layouts.
+        ///
+        /// # Safety
+        ///
+        /// - The same requirements as [`Allocator::grow`] apply, except that `new_layout`
+        ///   can be any size.
+        #[inline]
+        unsafe fn reallocate(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            if old_layout.size() == 0 || new_layout.size() == 0 {
+                let new = self.allocate(new_layout)?;
+
+                // SAFETY: The caller ensures that `ptr` is valid for the old layout, and
+                //         the new allocation can't overlap with it.
+                unsafe {
+                    let len = old_layout.size().min(new_layout.size());
+
+                    new.cast::<u8>().copy_from_nonoverlapping(ptr, len);
+                    self.deallocate(ptr, old_layout);
+                }
+
+                return Ok(new);
+            }
+
+            // SAFETY: The caller ensures that `ptr` was allocated with `old_layout`, and
+            //         neither layout is zero-sized.
+            let ptr = unsafe { realloc(ptr.as_ptr(), old_layout, new_layout.size()) };
+            let ptr = NonNull::new(ptr).ok_or(AllocError)?;
+
+            Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+        }
+    }
+}
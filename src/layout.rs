@@ -1,4 +1,9 @@
-use core::{alloc::Layout, mem, num::NonZeroUsize};
+use core::{
+    alloc::Layout,
+    fmt, hint, mem,
+    num::NonZeroUsize,
+    ops::{Deref, DerefMut},
+};
 
 /// Helper trait for quickly obtaining the memory layout information of a type.
 ///
@@ -16,6 +21,17 @@ pub trait HasLayout: Sized {
     /// The memory layout of the type.
     const LAYOUT: Layout = Layout::new::<Self>();
 
+    /// Whether or not [`Self`] is zero-sized.
+    const IS_ZST: bool = <Self as HasLayout>::SIZE == 0;
+
+    /// The largest length of a slice of [`Self`] that doesn't exceed `isize::MAX` bytes.
+    ///
+    /// For zero-sized types this is `usize::MAX`.
+    const MAX_SLICE_LEN: usize = match <Self as HasLayout>::SIZE {
+        0 => usize::MAX,
+        size => isize::MAX as usize / size,
+    };
+
     /// The amount of bits alignment bits for a given pointer to [`Self`].
     const ALIGN_BITS: u32 = mem::align_of::<Self>().trailing_zeros();
 
@@ -41,6 +57,444 @@ pub trait HasLayout: Sized {
 
     /// A bitmask of the bits actually used to address a pointer of [`Self`].
     const PTR_MASK: usize = !<Self as HasLayout>::TAG_MASK;
+
+    /// The memory layout of an array of `n` elements of [`Self`].
+    ///
+    /// Returns `None` if the size of the array would overflow `isize::MAX`.
+    #[inline]
+    #[must_use]
+    fn array_layout(n: usize) -> Option<Layout> {
+        array_layout::<Self>(n)
+    }
 }
 
 impl<T> HasLayout for T {}
+
+/// Assert that `T` has at least `bits` alignment bits.
+///
+/// This is meant to be evaluated at compile time, see [`assert_tag_bits!`].
+///
+/// # Panics
+///
+/// Panics if `T::ALIGN_BITS < bits`.
+#[inline]
+#[track_caller]
+pub const fn assert_tag_bits<T>(bits: u32) {
+    assert!(
+        T::ALIGN_BITS >= bits,
+        "not enough alignment bits for tagging"
+    );
+}
+
+/// Fail compilation if a type doesn't have at least `N` alignment bits.
+///
+/// This works for generic types as well, in which case the error happens when the
+/// surrounding function is instantiated with a type that is not aligned enough.
+#[macro_export]
+macro_rules! assert_tag_bits {
+    ($t:ty, $bits:expr $(,)?) => {
+        const { $crate::layout::assert_tag_bits::<$t>($bits) }
+    };
+}
+
+/// Marker for types that have no padding bytes, and for which every bit pattern is valid.
+///
+/// Byte-casting APIs are gated on this trait.
+///
+/// # Safety
+///
+/// - The type must not contain any padding bytes, including trailing padding.
+/// - Every bit pattern of the type's size must be a valid value of the type.
+/// - The type must not contain any interior mutability.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! pod {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            // SAFETY: Primitive integers and floats have no padding, and every
+            //         bit pattern is valid.
+            unsafe impl Pod for $ty {}
+        )*
+    };
+}
+
+pod!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    ()
+);
+
+// SAFETY: Arrays have no padding between their elements, and `T` has no padding
+//         of its own.
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Compute the memory layout of an array of `n` elements of `T`.
+///
+/// Returns `None` if the size of the array would overflow `isize::MAX`.
+#[inline]
+#[must_use]
+pub const fn array_layout<T>(n: usize) -> Option<Layout> {
+    let size = match T::SIZE.checked_mul(n) {
+        Some(size) => size,
+        None => return None,
+    };
+
+    match Layout::from_size_align(size, T::ALIGN.get()) {
+        Ok(layout) => Some(layout),
+        Err(_) => None,
+    }
+}
+
+/// Compute the size of `layout` rounded up to a multiple of its alignment.
+#[inline]
+#[must_use]
+pub const fn padded_size(layout: Layout) -> usize {
+    let mask = layout.align() - 1;
+
+    // A layout's size can never exceed `isize::MAX` once rounded up to its alignment,
+    // so this can never overflow.
+    (layout.size() + mask) & !mask
+}
+
+/// Compute the memory layout of `n` instances of `layout` placed back to back, along with
+/// the distance between the start of each instance.
+///
+/// Returns `None` if the size of the result would overflow `isize::MAX`.
+#[inline]
+#[must_use]
+pub const fn repeat_layout(layout: Layout, n: usize) -> Option<(Layout, usize)> {
+    let stride = padded_size(layout);
+
+    let size = match stride.checked_mul(n) {
+        Some(size) => size,
+        None => return None,
+    };
+
+    match Layout::from_size_align(size, layout.align()) {
+        Ok(layout) => Some((layout, stride)),
+        Err(_) => None,
+    }
+}
+
+/// Compute the layout of an `H` immediately followed by an array of `cap` elements of `T`,
+/// along with the offset of the array.
+///
+/// Returns `None` if the size of the allocation would overflow `isize::MAX`.
+#[inline]
+#[must_use]
+pub const fn layout_for_capacity<H, T>(cap: usize) -> Option<(Layout, usize)> {
+    let align = if H::ALIGN.get() > T::ALIGN.get() {
+        H::ALIGN.get()
+    } else {
+        T::ALIGN.get()
+    };
+
+    // The alignment of `T` is a power of two, so this can only overflow if the
+    // size of `H` is already close to `usize::MAX`, which is impossible.
+    let offset = (H::SIZE + T::ALIGN_MASK) & !T::ALIGN_MASK;
+
+    let array = match array_layout::<T>(cap) {
+        Some(array) => array,
+        None => return None,
+    };
+
+    let size = match offset.checked_add(array.size()) {
+        Some(size) => size,
+        None => return None,
+    };
+
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => Some((layout, offset)),
+        Err(_) => None,
+    }
+}
+
+/// Compute the layout of an `H` immediately followed by an array of `cap` elements of `T`,
+/// along with the offset of the array, without checking for overflow.
+///
+/// # Safety
+///
+/// - The caller must ensure that the size of the allocation does not overflow `isize::MAX`,
+///   see [`layout_for_capacity`].
+#[inline]
+#[must_use]
+#[track_caller]
+pub const unsafe fn layout_for_capacity_unchecked<H, T>(cap: usize) -> (Layout, usize) {
+    match layout_for_capacity::<H, T>(cap) {
+        Some(layout) => layout,
+        None if cfg!(debug_assertions) => panic!("capacity overflow"),
+        // SAFETY: The caller ensures that the layout does not overflow.
+        None => unsafe { hint::unreachable_unchecked() },
+    }
+}
+
+/// Companion to [`HasLayout`] for obtaining the memory layout information of a value
+/// whose type may be unsized, such as `[T]` or `dyn Trait`.
+///
+/// Everything is computed at runtime from the value's metadata.
+pub trait UnsizedLayout {
+    /// The memory layout of the value.
+    #[inline]
+    #[must_use]
+    fn value_layout(&self) -> Layout {
+        Layout::for_value(self)
+    }
+
+    /// The amount of alignment bits for a pointer to the value.
+    #[inline]
+    #[must_use]
+    fn value_align_bits(&self) -> u32 {
+        mem::align_of_val(self).trailing_zeros()
+    }
+
+    /// Whether or not a properly aligned pointer to the value can be used for pointer tagging.
+    #[inline]
+    #[must_use]
+    fn value_tagging_allowed(&self) -> bool {
+        self.value_align_bits() != 0
+    }
+
+    /// A bitmask of the alignment bits for a pointer to the value.
+    #[inline]
+    #[must_use]
+    fn value_tag_mask(&self) -> usize {
+        mem::align_of_val(self) - 1
+    }
+
+    /// A bitmask of the bits actually used to address a pointer to the value.
+    #[inline]
+    #[must_use]
+    fn value_ptr_mask(&self) -> usize {
+        !self.value_tag_mask()
+    }
+}
+
+impl<T: ?Sized> UnsizedLayout for T {}
+
+/// A `T` that is over-aligned to at least `N` bytes.
+///
+/// Since the alignment is real, [`HasLayout`] reports it, and tagged pointers to an
+/// `Aligned<T, N>` get `N.trailing_zeros()` tag bits regardless of the alignment of `T`.
+///
+/// `N` must be a power of two no larger than `1 << 29`.
+#[repr(C)]
+pub struct Aligned<T, const N: usize>
+where
+    Alignment<N>: ValidAlignment,
+{
+    _align: [<Alignment<N> as ValidAlignment>::Marker; 0],
+    value: T,
+}
+
+impl<T, const N: usize> Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    /// Wrap a value.
+    #[inline]
+    #[must_use]
+    pub const fn new(value: T) -> Aligned<T, N> {
+        Aligned { _align: [], value }
+    }
+
+    /// Unwrap the value.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Get a reference to the value.
+    #[inline]
+    #[must_use]
+    pub const fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Get a mutable reference to the value.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T, const N: usize> Deref for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, const N: usize> DerefMut for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T, const N: usize> From<T> for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn from(value: T) -> Self {
+        Aligned::new(value)
+    }
+}
+
+impl<T: Default, const N: usize> Default for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn default() -> Self {
+        Aligned::new(T::default())
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Aligned::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, const N: usize> Copy for Aligned<T, N> where Alignment<N>: ValidAlignment {}
+
+impl<T: PartialEq, const N: usize> PartialEq for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for Aligned<T, N> where Alignment<N>: ValidAlignment {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: core::hash::Hash, const N: usize> core::hash::Hash for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for Aligned<T, N>
+where
+    Alignment<N>: ValidAlignment,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Aligned").field(&self.value).finish()
+    }
+}
+
+/// A type level alignment of `N` bytes, used with [`Aligned`].
+pub struct Alignment<const N: usize>;
+
+/// Implemented for every [`Alignment`] that `#[repr(align)]` accepts.
+pub trait ValidAlignment: sealed::Sealed {
+    /// A zero-sized type with the alignment.
+    type Marker: Copy;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! alignments {
+    ($($name:ident = $align:literal),* $(,)?) => {
+        $(
+            #[doc(hidden)]
+            #[derive(Clone, Copy)]
+            #[repr(align($align))]
+            pub struct $name;
+
+            impl sealed::Sealed for Alignment<$align> {}
+
+            impl ValidAlignment for Alignment<$align> {
+                type Marker = $name;
+            }
+        )*
+    };
+}
+
+alignments! {
+    Align1 = 1,
+    Align2 = 2,
+    Align4 = 4,
+    Align8 = 8,
+    Align16 = 16,
+    Align32 = 32,
+    Align64 = 64,
+    Align128 = 128,
+    Align256 = 256,
+    Align512 = 512,
+    Align1024 = 1024,
+    Align2048 = 2048,
+    Align4096 = 4096,
+    Align8192 = 8192,
+    Align16384 = 16384,
+    Align32768 = 32768,
+    Align65536 = 65536,
+    Align131072 = 131072,
+    Align262144 = 262144,
+    Align524288 = 524288,
+    Align1048576 = 1048576,
+    Align2097152 = 2097152,
+    Align4194304 = 4194304,
+    Align8388608 = 8388608,
+    Align16777216 = 16777216,
+    Align33554432 = 33554432,
+    Align67108864 = 67108864,
+    Align134217728 = 134217728,
+    Align268435456 = 268435456,
+    Align536870912 = 536870912,
+}
@@ -2,9 +2,14 @@
 
 extern crate alloc;
 
-// #[cfg(feature = "std")]
-// extern crate std;
+#[cfg(feature = "std")]
+extern crate std;
 
-mod layout;
+pub mod allocator;
+pub mod layout;
 mod raw;
-mod tag_ptr;
+pub mod slice;
+mod strict;
+mod sync;
+pub mod tag_ptr;
+pub mod vec;
@@ -1,24 +1,486 @@
-use core::sync::atomic::AtomicUsize;
+use alloc::alloc::handle_alloc_error;
+use core::{alloc::Layout, mem, ptr::NonNull, sync::atomic::Ordering};
 
-use crate::tag_ptr::TagPtr;
+use crate::{
+    allocator::Allocator,
+    layout::{layout_for_capacity, layout_for_capacity_unchecked, HasLayout},
+    strict,
+    tag_ptr::{AtomicTagPtr, Tag, TagEncode, TagPtr},
+    vec::{RefCount, TryReserveError},
+};
 
 /// Header for a shared vec.
 #[repr(C)]
-pub struct Header<T> {
+pub struct Header<T, R> {
     /// Capacity of the vector.
     pub cap: usize,
     /// Length of the vector.
     pub len: usize,
     /// Reference count.
-    pub ref_count: AtomicUsize,
+    pub ref_count: R,
     /// Start of the data in the vector.
     pub data: [T; 0],
 }
 
+impl<T, R> Header<T, R> {
+    /// Offset of the capacity in the header.
+    #[allow(dead_code)]
+    pub const CAP_OFFSET: usize = mem::offset_of!(Header<T, R>, cap);
+
+    /// Offset of the length in the header.
+    #[allow(dead_code)]
+    pub const LEN_OFFSET: usize = mem::offset_of!(Header<T, R>, len);
+
+    /// Offset of the reference count in the header.
+    #[allow(dead_code)]
+    pub const REF_COUNT_OFFSET: usize = mem::offset_of!(Header<T, R>, ref_count);
+
+    /// Offset of the data in the header.
+    pub const DATA_OFFSET: usize = mem::offset_of!(Header<T, R>, data);
+
+    /// Compute the layout of a header with room for `cap` elements, along with the
+    /// offset of the data.
+    #[inline]
+    #[must_use]
+    pub const fn layout(cap: usize) -> Option<(Layout, usize)> {
+        layout_for_capacity::<Header<T, R>, T>(cap)
+    }
+
+    /// Compute the layout of a header with room for `cap` elements, along with the
+    /// offset of the data, without checking for overflow.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the layout does not overflow, see [`Header::layout`].
+    #[inline]
+    #[must_use]
+    pub const unsafe fn layout_unchecked(cap: usize) -> (Layout, usize) {
+        // SAFETY: The caller ensures that this is valid.
+        unsafe { layout_for_capacity_unchecked::<Header<T, R>, T>(cap) }
+    }
+
+    /// Whether the allocation of a `Vec<T>` can be reused for a header, and the other
+    /// way around.
+    ///
+    /// This requires that `T` is not zero-sized, that the header is no more aligned than
+    /// `T`, and that the header is a whole number of elements long, so that the layout of
+    /// the header matches the layout of the `Vec<T>`'s allocation.
+    pub const VEC_COMPATIBLE: bool = !T::IS_ZST
+        && mem::align_of::<Header<T, R>>() == T::ALIGN.get()
+        && Self::DATA_OFFSET % T::SIZE == 0;
+
+    /// Compute the capacity of a header that exactly fills the allocation of a `Vec<T>`
+    /// with capacity `cap`, if the allocation can be reused for one.
+    #[inline]
+    #[must_use]
+    pub const fn capacity_in_vec(cap: usize) -> Option<usize> {
+        if !Self::VEC_COMPATIBLE {
+            return None;
+        }
+
+        cap.checked_sub(Self::DATA_OFFSET / T::SIZE)
+    }
+
+    /// Compute the capacity of a `Vec<T>` that exactly fills the allocation of a header
+    /// with capacity `cap`, if the allocation can be reused for one.
+    #[inline]
+    #[must_use]
+    pub const fn vec_capacity(cap: usize) -> Option<usize> {
+        if !Self::VEC_COMPATIBLE {
+            return None;
+        }
+
+        cap.checked_add(Self::DATA_OFFSET / T::SIZE)
+    }
+}
+
+/// How the elements of a shared vec are stored, kept in the tag of [`Data`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// The elements are stored after a [`Header`], which may be shared.
+    Heap = 0,
+    /// The elements are stored inline in the handle, and the address is the length.
+    Inline = 1,
+    /// The elements are borrowed from a `&'static [T]`.
+    Static = 2,
+    /// The elements are stored after a [`Header`], and this is the only handle to it.
+    ///
+    /// This is only set by handles that know they are unique, such as freshly allocated
+    /// ones, so that they can skip checking the reference count.
+    Unique = 3,
+}
+
+impl<T, R> TagEncode<Header<T, R>> for Kind {
+    const MAX: usize = Kind::Unique as usize;
+
+    #[inline]
+    fn encode(self) -> Tag<Header<T, R>> {
+        let () = <Self as TagEncode<Header<T, R>>>::FITS;
+
+        // SAFETY: `FITS` ensures that every kind fits within the alignment bits.
+        unsafe { Tag::new_unchecked(self as usize) }
+    }
+
+    #[inline]
+    fn decode(tag: Tag<Header<T, R>>) -> Self {
+        match tag.get() {
+            0 => Kind::Heap,
+            1 => Kind::Inline,
+            2 => Kind::Static,
+            3 => Kind::Unique,
+            _ => unreachable!("invalid tag for `Kind`"),
+        }
+    }
+}
+
 /// Pointer to the start of the data in a shared vec.
 #[repr(transparent)]
-pub struct Data<T> {
-    /// Despite this saying a tagged pointer to `Header<T>`, it is actually a pointer to
+pub struct Data<T, R> {
+    /// Despite this saying a tagged pointer to `Header<T, R>`, it is actually a pointer to
     /// the data field in the header.
-    ptr: TagPtr<Header<T>>,
+    ptr: TagPtr<Header<T, R>>,
+}
+
+impl<T, R: RefCount> Data<T, R> {
+    /// Allocate a header with room for `cap` elements and a reference count of one
+    /// from `alloc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the allocation would overflow `isize::MAX`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn allocate<A: Allocator>(cap: usize, alloc: &A) -> Data<T, R> {
+        match Data::try_allocate(cap, alloc) {
+            Ok(data) => data,
+            Err(error) => handle_reserve_error(error),
+        }
+    }
+
+    /// Try to allocate a header with room for `cap` elements and a reference count of one
+    /// from `alloc`.
+    #[inline]
+    pub fn try_allocate<A: Allocator>(
+        cap: usize,
+        alloc: &A,
+    ) -> Result<Data<T, R>, TryReserveError> {
+        let Some((layout, _)) = Header::<T, R>::layout(cap) else {
+            return Err(TryReserveError::CapacityOverflow);
+        };
+
+        let Ok(ptr) = alloc.allocate(layout) else {
+            return Err(TryReserveError::AllocError { layout });
+        };
+
+        // SAFETY: The allocation is valid for a header with room for `cap` elements.
+        Ok(unsafe { Data::init(ptr.cast(), cap, 0) })
+    }
+
+    /// Try to allocate a header with room for `cap` elements and a reference count of one
+    /// from `alloc`, where the room for the elements is filled with zero bytes.
+    #[inline]
+    pub fn try_allocate_zeroed<A: Allocator>(
+        cap: usize,
+        alloc: &A,
+    ) -> Result<Data<T, R>, TryReserveError> {
+        let Some((layout, _)) = Header::<T, R>::layout(cap) else {
+            return Err(TryReserveError::CapacityOverflow);
+        };
+
+        let Ok(ptr) = alloc.allocate_zeroed(layout) else {
+            return Err(TryReserveError::AllocError { layout });
+        };
+
+        // SAFETY: The allocation is valid for a header with room for `cap` elements.
+        Ok(unsafe { Data::init(ptr.cast(), cap, 0) })
+    }
+
+    /// Write a header with a reference count of one into an allocation.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `header` points to an allocation made with the
+    ///   layout of a header with room for `cap` elements, see [`Header::layout`], and
+    ///   that the first `len` elements after the header are initialized.
+    #[inline]
+    #[must_use]
+    pub unsafe fn init(header: NonNull<Header<T, R>>, cap: usize, len: usize) -> Data<T, R> {
+        // SAFETY: The caller ensures that the allocation is valid for a header.
+        unsafe {
+            header.write(Header {
+                cap,
+                len,
+                ref_count: R::one(),
+                data: [],
+            });
+
+            Data::from_header(header, Kind::Unique.encode())
+        }
+    }
+
+    /// Deallocate the header without dropping any elements.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the header was allocated by [`Data::allocate`] from
+    ///   `alloc`, and that it is not used again.
+    #[inline]
+    pub unsafe fn deallocate<A: Allocator>(self, alloc: &A) {
+        let header = self.header();
+
+        // SAFETY: The caller ensures that the header is valid, and the layout was
+        //         valid when it was allocated.
+        unsafe {
+            let (layout, _) = Header::<T, R>::layout_unchecked(header.as_ref().cap);
+
+            alloc.deallocate(header.cast(), layout);
+        }
+    }
+
+    /// Change the capacity of the header, moving the elements along with it.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the header was allocated by [`Data::allocate`] from
+    ///   `alloc`, that there are no other handles to it, and that `cap` is at least
+    ///   its length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the allocation would overflow `isize::MAX`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn reallocate<A: Allocator>(self, cap: usize, alloc: &A) -> Data<T, R> {
+        // SAFETY: The caller ensures that this is valid.
+        match unsafe { self.try_reallocate(cap, alloc) } {
+            Ok(data) => data,
+            Err(error) => handle_reserve_error(error),
+        }
+    }
+
+    /// Try to change the capacity of the header, moving the elements along with it.
+    ///
+    /// On failure the header is left untouched.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as [`Data::reallocate`] apply.
+    #[inline]
+    pub unsafe fn try_reallocate<A: Allocator>(
+        self,
+        cap: usize,
+        alloc: &A,
+    ) -> Result<Data<T, R>, TryReserveError> {
+        let Some((new_layout, _)) = Header::<T, R>::layout(cap) else {
+            return Err(TryReserveError::CapacityOverflow);
+        };
+
+        let header = self.header();
+
+        // SAFETY: The caller ensures that the header is valid and unique, and the layout
+        //         was valid when it was allocated. Both layouts have the same alignment.
+        unsafe {
+            let (layout, _) = Header::<T, R>::layout_unchecked(header.as_ref().cap);
+
+            let result = if new_layout.size() >= layout.size() {
+                alloc.grow(header.cast(), layout, new_layout)
+            } else {
+                alloc.shrink(header.cast(), layout, new_layout)
+            };
+
+            let Ok(ptr) = result else {
+                return Err(TryReserveError::AllocError { layout: new_layout });
+            };
+
+            let header = ptr.cast::<Header<T, R>>();
+
+            (*header.as_ptr()).cap = cap;
+
+            Ok(Data::from_header(header, self.tag()))
+        }
+    }
+
+    /// Create a pointer to the data of a header.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `header` points to an allocation that is valid for
+    ///   a header with some capacity, see [`Header::layout`].
+    #[inline]
+    #[must_use]
+    pub unsafe fn from_header(header: NonNull<Header<T, R>>, tag: Tag<Header<T, R>>) -> Data<T, R> {
+        // SAFETY: The caller ensures that the data field is within the allocation, and
+        //         since the header is aligned, so is the data field.
+        let ptr = unsafe { header.byte_add(Header::<T, R>::DATA_OFFSET) };
+
+        Data {
+            ptr: TagPtr::new(ptr, tag),
+        }
+    }
+
+    /// Create a dangling marker for elements that are not stored on the heap.
+    #[inline]
+    #[must_use]
+    pub const fn dangling(tag: Tag<Header<T, R>>) -> Data<T, R> {
+        Data {
+            ptr: TagPtr::dangling(tag),
+        }
+    }
+
+    /// Create a marker for elements that are stored inline, with a length of `len`.
+    #[inline]
+    #[must_use]
+    pub fn inline(len: usize) -> Data<T, R> {
+        let addr = (len + 1) << Header::<T, R>::ALIGN_BITS;
+
+        // SAFETY: The address is never zero, and it is aligned for a header.
+        let ptr = unsafe { NonNull::new_unchecked(strict::without_provenance_mut(addr)) };
+
+        Data {
+            ptr: TagPtr::new(ptr, Kind::Inline.encode()),
+        }
+    }
+
+    /// Get the length of elements that are stored inline.
+    #[inline]
+    #[must_use]
+    pub fn inline_len(&self) -> usize {
+        debug_assert_eq!(self.kind(), Kind::Inline);
+
+        (strict::addr(self.ptr.ptr().as_ptr()) >> Header::<T, R>::ALIGN_BITS) - 1
+    }
+
+    /// Get how the elements are stored.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> Kind {
+        self.ptr.tag_as()
+    }
+
+    /// Change how the elements are stored.
+    #[inline]
+    #[must_use]
+    pub fn with_kind(self, kind: Kind) -> Data<T, R> {
+        Data {
+            ptr: self.ptr.with_tag_as(kind),
+        }
+    }
+
+    /// Get a pointer to the header.
+    ///
+    /// This must only be called when the elements are stored on the heap.
+    #[inline]
+    #[must_use]
+    pub fn header(&self) -> NonNull<Header<T, R>> {
+        debug_assert!(matches!(self.kind(), Kind::Heap | Kind::Unique));
+
+        // SAFETY: Creating a `Data<T, R>` requires that it points to the data field of a header.
+        unsafe { self.ptr.ptr().byte_sub(Header::<T, R>::DATA_OFFSET) }
+    }
+
+    /// Get a pointer to the data.
+    #[inline]
+    #[must_use]
+    pub fn data(&self) -> NonNull<T> {
+        self.ptr.ptr().cast()
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(&self) -> Tag<Header<T, R>> {
+        self.ptr.tag()
+    }
+
+    /// Get the raw tagged pointer, with the kind stored in the alignment bits.
+    #[inline]
+    #[must_use]
+    pub fn into_raw(self) -> *mut Header<T, R> {
+        self.ptr.into_raw()
+    }
+
+    /// Reconstruct a [`Data`] from its raw tagged pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `raw` was returned by [`Data::into_raw`].
+    #[inline]
+    #[must_use]
+    pub unsafe fn from_raw(raw: *mut Header<T, R>) -> Data<T, R> {
+        Data {
+            // SAFETY: The caller ensures that this came from a valid tagged pointer.
+            ptr: unsafe { TagPtr::from_raw_unchecked(raw) },
+        }
+    }
+}
+
+/// A [`Data`] that can be updated through a shared reference.
+#[repr(transparent)]
+pub struct AtomicData<T, R> {
+    ptr: AtomicTagPtr<Header<T, R>>,
+}
+
+impl<T, R> AtomicData<T, R> {
+    /// Wrap a [`Data`].
+    #[inline]
+    #[must_use]
+    pub const fn new(data: Data<T, R>) -> AtomicData<T, R> {
+        AtomicData {
+            ptr: AtomicTagPtr::new(data.ptr),
+        }
+    }
+
+    /// Get the [`Data`].
+    ///
+    /// This is a relaxed load, so it doesn't synchronize with anything.
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> Data<T, R> {
+        Data {
+            ptr: self.ptr.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Replace the [`Data`].
+    #[inline]
+    pub fn set(&mut self, data: Data<T, R>) {
+        *self.ptr.get_mut() = data.ptr;
+    }
+
+    /// Replace the [`Data`] through a shared reference.
+    ///
+    /// This is a relaxed store, so it doesn't synchronize with anything.
+    #[inline]
+    pub fn store(&self, data: Data<T, R>) {
+        self.ptr.store(data.ptr, Ordering::Relaxed);
+    }
+}
+
+impl<T, R> Clone for Data<T, R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, R> Copy for Data<T, R> {}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn capacity_overflow() -> ! {
+    panic!("capacity overflow")
+}
+
+/// Panic or abort on a failed allocation, like the infallible `Vec` methods do.
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn handle_reserve_error(error: TryReserveError) -> ! {
+    match error {
+        TryReserveError::CapacityOverflow => capacity_overflow(),
+        TryReserveError::AllocError { layout } => handle_alloc_error(layout),
+    }
 }
@@ -0,0 +1,340 @@
+//! A reference counted, immutable slice.
+
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    cell::Cell,
+    cmp, fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, Index, RangeBounds},
+    slice::{self, SliceIndex},
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    sync::AtomicUsize,
+    vec::{RefCount, SharedVec},
+};
+
+/// An immutable handle to reference counted elements, where cloning only bumps the
+/// reference count.
+///
+/// This is a [`SharedVec`] that has been frozen with [`SharedVec::freeze`], which makes
+/// it explicit that the elements are only shared from then on. It has no methods that
+/// mutate, reserve or report the capacity. Freezing a vector shrinks it to fit, so a
+/// slice that is the only handle to its elements keeps no spare capacity, while one that
+/// shares them keeps the allocation as is rather than copying. It still has the same
+/// layout as the vector, which makes [`SharedSlice::thaw`] free; it gives a vector back
+/// that clones the elements once it is mutated if they are still shared.
+pub struct SharedSlice<T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    vec: SharedVec<T, R, A>,
+}
+
+/// A [`SharedSlice`] with a non-atomic reference count, which can't be sent to or shared
+/// with other threads.
+pub type LocalSharedSlice<T, A = Global> = SharedSlice<T, Cell<usize>, A>;
+
+impl<T, R: RefCount> SharedSlice<T, R> {
+    /// Create an empty slice, without allocating.
+    #[inline]
+    #[must_use]
+    pub fn new() -> SharedSlice<T, R> {
+        SharedVec::new().freeze()
+    }
+
+    /// Create a slice that borrows static data, without allocating.
+    #[inline]
+    #[must_use]
+    pub const fn from_static(elems: &'static [T]) -> SharedSlice<T, R> {
+        SharedSlice {
+            vec: SharedVec::from_static(elems),
+        }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> SharedSlice<T, R, A> {
+    /// Create an empty slice that uses `alloc`, without allocating.
+    #[inline]
+    #[must_use]
+    pub fn new_in(alloc: A) -> SharedSlice<T, R, A> {
+        SharedVec::new_in(alloc).freeze()
+    }
+
+    /// Create a slice that borrows static data, and uses `alloc` once it is thawed and
+    /// mutated.
+    #[inline]
+    #[must_use]
+    pub const fn from_static_in(elems: &'static [T], alloc: A) -> SharedSlice<T, R, A> {
+        SharedSlice {
+            vec: SharedVec::from_static_in(elems, alloc),
+        }
+    }
+
+    /// Get the allocator the elements were allocated from.
+    #[inline]
+    #[must_use]
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+    /// Get the amount of elements in the slice.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns whether the slice has no elements.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Get the amount of handles to the elements, see [`SharedVec::strong_count`].
+    #[inline]
+    #[must_use]
+    pub fn strong_count(&self) -> usize {
+        self.vec.strong_count()
+    }
+
+    /// Get the elements as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        self.vec.as_slice()
+    }
+
+    /// Get the elements as an array, if there are exactly `N` of them.
+    #[inline]
+    #[must_use]
+    pub fn as_array<const N: usize>(&self) -> Option<&[T; N]> {
+        self.vec.as_array()
+    }
+
+    /// Turn the slice back into a vector, without copying.
+    #[inline]
+    #[must_use]
+    pub fn thaw(self) -> SharedVec<T, R, A> {
+        self.vec
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> SharedSlice<T, R, A> {
+    /// Create a handle to a subrange of the elements, see [`SharedVec::slice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> SharedSlice<T, R, A> {
+        self.vec.slice(range).freeze()
+    }
+
+    /// Create a handle to the elements in `range`, or `None` if it is out of bounds, see
+    /// [`SharedVec::get_shared`].
+    #[inline]
+    #[must_use]
+    pub fn get_shared(&self, range: impl RangeBounds<usize>) -> Option<SharedSlice<T, R, A>> {
+        self.vec.get_shared(range).map(SharedVec::freeze)
+    }
+
+    /// Create a handle to the elements in `sub`, which must be a subslice of this slice,
+    /// see [`SharedVec::slice_ref`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sub` is not within the elements of this slice.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn slice_ref(&self, sub: &[T]) -> SharedSlice<T, R, A> {
+        self.vec.slice_ref(sub).freeze()
+    }
+
+    /// Split the slice into two at `mid`, see [`SharedVec::split_at`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn split_at(&self, mid: usize) -> (SharedSlice<T, R, A>, SharedSlice<T, R, A>) {
+        let (left, right) = self.vec.split_at(mid);
+
+        (left.freeze(), right.freeze())
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> Clone for SharedSlice<T, R, A> {
+    /// Create another handle to the elements, see [`SharedVec::clone`].
+    #[inline]
+    fn clone(&self) -> Self {
+        SharedSlice {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator + Default> Default for SharedSlice<T, R, A> {
+    #[inline]
+    fn default() -> Self {
+        SharedSlice::new_in(A::default())
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> From<SharedVec<T, R, A>> for SharedSlice<T, R, A> {
+    #[inline]
+    fn from(mut vec: SharedVec<T, R, A>) -> Self {
+        vec.shrink_to_fit();
+
+        SharedSlice { vec }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> From<SharedSlice<T, R, A>> for SharedVec<T, R, A> {
+    #[inline]
+    fn from(slice: SharedSlice<T, R, A>) -> Self {
+        slice.vec
+    }
+}
+
+impl<T, R: RefCount> From<Vec<T>> for SharedSlice<T, R> {
+    /// Convert a `Vec<T>` into a slice, reusing its allocation when possible, see
+    /// [`SharedVec::from`].
+    #[inline]
+    fn from(vec: Vec<T>) -> Self {
+        SharedVec::from(vec).freeze()
+    }
+}
+
+impl<T, R: RefCount> FromIterator<T> for SharedSlice<T, R> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SharedVec::from_iter(iter).freeze()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Deref for SharedSlice<T, R, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> AsRef<[T]> for SharedSlice<T, R, A> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Borrow<[T]> for SharedSlice<T, R, A> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, R: RefCount, A: Allocator> Index<I> for SharedSlice<T, R, A> {
+    type Output = I::Output;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: I) -> &I::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<'a, T, R: RefCount, A: Allocator> IntoIterator for &'a SharedSlice<T, R, A> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.as_slice().iter()
+    }
+}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for SharedSlice<T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<T: Hash, R: RefCount, A: Allocator> Hash for SharedSlice<T, R, A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<T: Eq, R: RefCount, A: Allocator> Eq for SharedSlice<T, R, A> {}
+
+impl<T, R1, R2, A1, A2> PartialOrd<SharedSlice<T, R2, A2>> for SharedSlice<T, R1, A1>
+where
+    T: PartialOrd,
+    R1: RefCount,
+    R2: RefCount,
+    A1: Allocator,
+    A2: Allocator,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &SharedSlice<T, R2, A2>) -> Option<cmp::Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord, R: RefCount, A: Allocator> Ord for SharedSlice<T, R, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T, U, R1, R2, A1, A2> PartialEq<SharedSlice<U, R2, A2>> for SharedSlice<T, R1, A1>
+where
+    T: PartialEq<U>,
+    R1: RefCount,
+    R2: RefCount,
+    A1: Allocator,
+    A2: Allocator,
+{
+    #[inline]
+    fn eq(&self, other: &SharedSlice<U, R2, A2>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+macro_rules! impl_eq {
+    ($([$($vars:tt)*] $lhs:ty, $rhs:ty, |$a:ident, $b:ident| $eq:expr;)*) => {
+        $(
+            impl<T, U, R: RefCount, A: Allocator, $($vars)*> PartialEq<$rhs> for $lhs
+            where
+                T: PartialEq<U>,
+            {
+                #[inline]
+                fn eq(&self, other: &$rhs) -> bool {
+                    let ($a, $b) = (self, other);
+                    $eq
+                }
+            }
+        )*
+    };
+}
+
+impl_eq! {
+    [] SharedSlice<T, R, A>, [U], |a, b| a.as_slice() == b;
+    [] SharedSlice<T, R, A>, &[U], |a, b| a.as_slice() == *b;
+    [] SharedSlice<T, R, A>, Vec<U>, |a, b| a.as_slice() == b.as_slice();
+    [const N: usize] SharedSlice<T, R, A>, [U; N], |a, b| a.as_slice() == b.as_slice();
+    [const N: usize] SharedSlice<T, R, A>, &[U; N], |a, b| a.as_slice() == b.as_slice();
+    [] [T], SharedSlice<U, R, A>, |a, b| a == b.as_slice();
+    [] &[T], SharedSlice<U, R, A>, |a, b| *a == b.as_slice();
+    [] Vec<T>, SharedSlice<U, R, A>, |a, b| a.as_slice() == b.as_slice();
+}
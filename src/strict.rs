@@ -0,0 +1,79 @@
+//! Strict provenance operations on pointers.
+//!
+//! These use the standard library's strict provenance APIs, or [`sptr`] with the
+//! `sptr` feature for toolchains that predate them.
+
+/// Get the address of a pointer, without exposing its provenance.
+#[inline(always)]
+#[must_use]
+pub(crate) fn addr<T>(ptr: *mut T) -> usize {
+    #[cfg(not(feature = "sptr"))]
+    {
+        ptr.addr()
+    }
+
+    #[cfg(feature = "sptr")]
+    {
+        sptr::Strict::addr(ptr)
+    }
+}
+
+/// Create a new pointer with the provenance of `ptr` by mapping its address.
+#[inline(always)]
+#[must_use]
+pub(crate) fn map_addr<T>(ptr: *mut T, f: impl FnOnce(usize) -> usize) -> *mut T {
+    #[cfg(not(feature = "sptr"))]
+    {
+        ptr.map_addr(f)
+    }
+
+    #[cfg(feature = "sptr")]
+    {
+        sptr::Strict::map_addr(ptr, f)
+    }
+}
+
+/// Create a pointer with the given address and no provenance.
+#[inline(always)]
+#[must_use]
+pub(crate) const fn without_provenance_mut<T>(addr: usize) -> *mut T {
+    #[cfg(not(feature = "sptr"))]
+    {
+        core::ptr::without_provenance_mut(addr)
+    }
+
+    #[cfg(feature = "sptr")]
+    {
+        sptr::invalid_mut(addr)
+    }
+}
+
+/// Expose the provenance of a pointer and return its address.
+#[inline(always)]
+#[must_use]
+pub(crate) fn expose_provenance<T>(ptr: *mut T) -> usize {
+    #[cfg(not(feature = "sptr"))]
+    {
+        ptr.expose_provenance()
+    }
+
+    #[cfg(feature = "sptr")]
+    {
+        sptr::Strict::expose_addr(ptr)
+    }
+}
+
+/// Create a pointer with the given address, picking up previously exposed provenance.
+#[inline(always)]
+#[must_use]
+pub(crate) fn with_exposed_provenance_mut<T>(addr: usize) -> *mut T {
+    #[cfg(not(feature = "sptr"))]
+    {
+        core::ptr::with_exposed_provenance_mut(addr)
+    }
+
+    #[cfg(feature = "sptr")]
+    {
+        sptr::from_exposed_addr_mut(addr)
+    }
+}
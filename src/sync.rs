@@ -0,0 +1,15 @@
+//! Atomic types used by the crate.
+//!
+//! With the `portable-atomic` feature enabled these come from [`portable_atomic`],
+//! which lets targets without native atomic read-modify-write instructions
+//! (`thumbv6m`, RISC-V without the A extension, AVR, ...) still use them.
+//!
+//! That alone doesn't make every container available there: on 16-bit targets like AVR
+//! the header of a [`SharedVec`](crate::vec::SharedVec) isn't aligned enough to keep
+//! its kind in the pointer, unless the elements are aligned to at least 4 bytes.
+
+#[cfg(not(feature = "portable-atomic"))]
+pub(crate) use core::sync::atomic::{fence, AtomicPtr, AtomicUsize};
+
+#[cfg(feature = "portable-atomic")]
+pub(crate) use portable_atomic::{fence, AtomicPtr, AtomicUsize};
@@ -1,13 +1,37 @@
 use core::{
     fmt,
     hint::{assert_unchecked, unreachable_unchecked},
+    iter::FusedIterator,
     marker::PhantomData,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not},
     ptr::NonNull,
+    sync::atomic::Ordering,
 };
 
-use sptr::{invalid_mut, Strict};
+use alloc::boxed::Box;
 
-use crate::layout::HasLayout;
+use crate::{layout::HasLayout, strict, sync::AtomicPtr};
+
+mod bits;
+mod either;
+mod encode;
+#[cfg(target_pointer_width = "64")]
+mod high;
+mod nullable;
+mod refs;
+mod uninit;
+
+pub use bits::{TagN, TagPtrN};
+pub use either::TaggedEither;
+pub use encode::TagEncode;
+pub use nullable::NullableTagPtr;
+pub use refs::{TagMut, TagRef};
+
+#[cfg(feature = "derive")]
+pub use chering_derive::PointerTag;
+
+#[cfg(target_pointer_width = "64")]
+pub use high::{HighTag, HighTagPtr, WideTag, WideTagPtr, HIGH_BITS};
 
 /// A tagged pointer that stores a tag in the alignment bits of a pointer.
 ///
@@ -24,15 +48,140 @@ impl<T> TagPtr<T> {
     #[must_use]
     pub const fn dangling(tag: Tag<T>) -> TagPtr<T> {
         // SAFETY: Since `T::ALIGN` is never zero, `raw` is never null.
-        let raw = invalid_mut(T::ALIGN.get() | tag.get());
+        let raw = strict::without_provenance_mut(T::ALIGN.get() | tag.get());
         let raw = unsafe { NonNull::new_unchecked(raw) };
 
         TagPtr { raw }
     }
 
+    /// Get the raw composite representation of this tagged pointer, with the tag
+    /// stored in the alignment bits.
+    ///
+    /// The result has the same provenance as `self`, and can be turned back into a
+    /// tagged pointer with [`TagPtr::from_raw`].
+    #[inline]
+    #[must_use]
+    pub const fn into_raw(self) -> *mut T {
+        self.raw.as_ptr()
+    }
+
+    /// Reconstruct a tagged pointer from its raw composite representation.
+    ///
+    /// Returns `None` if the address without the tag is null.
+    #[inline]
+    #[must_use]
+    pub fn from_raw(raw: *mut T) -> Option<TagPtr<T>> {
+        if strict::addr(raw) & T::PTR_MASK != 0 {
+            NonNull::new(raw).map(|raw| TagPtr { raw })
+        } else {
+            None
+        }
+    }
+
+    /// Reconstruct a tagged pointer from its raw composite representation without
+    /// safety checks.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the address of `raw` without the tag is non-null,
+    ///   which is always the case for pointers returned by [`TagPtr::into_raw`].
+    #[inline]
+    #[must_use]
+    pub const unsafe fn from_raw_unchecked(raw: *mut T) -> TagPtr<T> {
+        // SAFETY: The caller ensures that the untagged address is non-null, and so is the
+        //         composite one.
+        TagPtr {
+            raw: unsafe { NonNull::new_unchecked(raw) },
+        }
+    }
+
+    /// Expose the provenance of this tagged pointer and return its raw composite address.
+    ///
+    /// The result can be turned back into a tagged pointer with
+    /// [`TagPtr::with_exposed_provenance`].
+    #[inline]
+    #[must_use]
+    pub fn expose_provenance(self) -> usize {
+        strict::expose_provenance(self.raw.as_ptr())
+    }
+
+    /// Reconstruct a tagged pointer from a raw composite address, picking up any
+    /// previously exposed provenance.
+    ///
+    /// Returns `None` if the address without the tag is null. Dereferencing the
+    /// result is only valid if its provenance was exposed, for instance with
+    /// [`TagPtr::expose_provenance`].
+    #[inline]
+    #[must_use]
+    pub fn with_exposed_provenance(addr: usize) -> Option<TagPtr<T>> {
+        TagPtr::from_raw(strict::with_exposed_provenance_mut(addr))
+    }
+
+    /// Create a tagged pointer from a reference.
+    ///
+    /// References are always aligned, so this can't fail, and unlike [`TagPtr::new`]
+    /// it is usable in `const` contexts, for instance to tag a pointer to a `static`.
+    #[inline]
+    #[must_use]
+    pub const fn from_ref(r: &T, tag: Tag<T>) -> TagPtr<T> {
+        // Since the address is aligned, adding the tag is the same as or-ing it in.
+        let raw = (r as *const T).cast_mut().wrapping_byte_add(tag.get());
+
+        // SAFETY: References are aligned and non-null, so inserting the tag will never
+        //         cause the address to be null.
+        TagPtr {
+            raw: unsafe { NonNull::new_unchecked(raw) },
+        }
+    }
+
+    /// Create a tagged pointer from a mutable reference.
+    ///
+    /// See [`TagPtr::from_ref`].
+    #[inline]
+    #[must_use]
+    pub const fn from_mut(r: &mut T, tag: Tag<T>) -> TagPtr<T> {
+        // Since the address is aligned, adding the tag is the same as or-ing it in.
+        let raw = (r as *mut T).wrapping_byte_add(tag.get());
+
+        // SAFETY: References are aligned and non-null, so inserting the tag will never
+        //         cause the address to be null.
+        TagPtr {
+            raw: unsafe { NonNull::new_unchecked(raw) },
+        }
+    }
+
+    /// Create a tagged pointer that owns the allocation of a [`Box`].
+    ///
+    /// The allocation is leaked unless the pointer is turned back into a box with
+    /// [`TagPtr::into_box`].
+    #[inline]
+    #[must_use]
+    pub fn from_box(boxed: Box<T>, tag: Tag<T>) -> TagPtr<T> {
+        // SAFETY: A box is always non-null and properly aligned.
+        unsafe { TagPtr::new_unchecked(NonNull::new_unchecked(Box::into_raw(boxed)), tag) }
+    }
+
+    /// Strip the tag and reconstitute the [`Box`] this pointer was created from.
+    ///
+    /// # Safety
+    ///
+    /// - The untagged pointer must have been created by [`TagPtr::from_box`] (or otherwise
+    ///   satisfy the requirements of [`Box::from_raw`]).
+    /// - The box must not already have been reconstituted from this pointer or a copy of it.
+    #[inline]
+    #[must_use]
+    pub unsafe fn into_box(self) -> Box<T> {
+        // SAFETY: The caller ensures that the untagged pointer owns a boxed `T`, and
+        //         stripping the tag keeps the provenance of the original box.
+        unsafe { Box::from_raw(self.ptr().as_ptr()) }
+    }
+
     /// Try to create a tagged pointer to a `T`.
     ///
     /// Returns `None` if `ptr` is not aligned for `T`.
+    ///
+    /// Checking the alignment of an arbitrary pointer isn't possible at compile time,
+    /// see [`TagPtr::from_ref`] for a `const` constructor.
     #[inline]
     #[must_use]
     pub fn try_new(ptr: NonNull<T>, tag: Tag<T>) -> Option<TagPtr<T>> {
@@ -40,10 +189,722 @@ impl<T> TagPtr<T> {
             // SAFETY: Since `ptr` is aligned and not null, the tag will never conflict
             //         with the address, and additionally inserting the tag will never
             //         cause the address to be null.
-            let raw = Strict::map_addr(ptr.as_ptr(), |addr| addr | tag.get());
+            let raw = strict::map_addr(ptr.as_ptr(), |addr| addr | tag.get());
+            let raw = unsafe { NonNull::new_unchecked(raw) };
+
+            Some(TagPtr { raw })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tagged pointer to a `T` without safety checks.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn new_unchecked(ptr: NonNull<T>, tag: Tag<T>) -> TagPtr<T> {
+        match TagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None if cfg!(debug_assertions) => panic!("`ptr` is not aligned"),
+            // SAFETY: The caller ensures that `ptr` is aligned properly.
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Create a tagged pointer to a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn new(ptr: NonNull<T>, tag: Tag<T>) -> TagPtr<T> {
+        match TagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None => panic!("`ptr` is not aligned"),
+        }
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(self) -> Tag<T> {
+        let tag = strict::addr(self.raw.as_ptr()) & T::TAG_MASK;
+
+        Tag::new(tag).unwrap()
+    }
+
+    /// Get the tag, decoded as an `E`.
+    ///
+    /// This fails to compile if the encoding of `E` doesn't fit within the alignment bits of `T`.
+    #[inline]
+    #[must_use]
+    pub fn tag_as<E: TagEncode<T>>(self) -> E {
+        let () = E::FITS;
+
+        E::decode(self.tag())
+    }
+
+    /// Get the actual pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The pointer is guaranteed to be properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    pub fn ptr(self) -> NonNull<T> {
+        let ptr = strict::map_addr(self.raw.as_ptr(), |addr| addr & T::PTR_MASK);
+
+        unsafe {
+            // SAFETY: Creating a tagged pointer requires that the pointer without the tag
+            //         is properly aligned for `T` and non-null.
+            assert_unchecked(ptr.is_aligned());
+
+            NonNull::new_unchecked(ptr)
+        }
+    }
+
+    /// Create a new tagged pointer with the same address as `self`.
+    ///
+    /// # Safety
+    ///
+    /// - The resulting pointer has the same provenance as `self`.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: Tag<T>) -> TagPtr<T> {
+        // SAFETY: We already know that `ptr` is valid, as it must be in order to construct
+        //         a tagged pointer.
+        unsafe { TagPtr::new_unchecked(self.ptr(), tag) }
+    }
+
+    /// Create a new tagged pointer with the same address as `self`, tagged with an encoded `tag`.
+    ///
+    /// This fails to compile if the encoding of `E` doesn't fit within the alignment bits of `T`.
+    #[inline]
+    #[must_use]
+    pub fn with_tag_as<E: TagEncode<T>>(self, tag: E) -> TagPtr<T> {
+        let () = E::FITS;
+
+        self.with_tag(tag.encode())
+    }
+
+    /// Create a new tagged pointer with the same tag as `self`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The resulting pointer has the same provenance as `ptr`, not `self`.
+    #[inline]
+    #[must_use]
+    pub unsafe fn with_ptr(self, ptr: NonNull<T>) -> TagPtr<T> {
+        debug_assert!(ptr.is_aligned(), "`ptr` is not aligned");
+
+        // SAFETY: The caller ensures that `ptr` is valid.
+        unsafe { TagPtr::new_unchecked(ptr, self.tag()) }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: Tag<T>) {
+        *self = self.with_tag(tag);
+    }
+
+    /// Create a new tagged pointer with the same address as `self`, with the tag
+    /// replaced by the result of `f`.
+    #[inline]
+    #[must_use]
+    pub fn map_tag(self, f: impl FnOnce(Tag<T>) -> Tag<T>) -> TagPtr<T> {
+        self.with_tag(f(self.tag()))
+    }
+
+    /// Update the tag in place with the result of `f`.
+    #[inline]
+    pub fn update_tag(&mut self, f: impl FnOnce(Tag<T>) -> Tag<T>) {
+        *self = self.map_tag(f);
+    }
+
+    /// Returns whether bit `n` of the tag is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn tag_bit(self, n: u32) -> bool {
+        self.tag().get_bit(n)
+    }
+
+    /// Set bit `n` of the tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[track_caller]
+    pub fn set_tag_bit(&mut self, n: u32) {
+        let mut tag = self.tag();
+        tag.set_bit(n);
+
+        self.set_tag(tag);
+    }
+
+    /// Clear bit `n` of the tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[track_caller]
+    pub fn clear_tag_bit(&mut self, n: u32) {
+        let mut tag = self.tag();
+        tag.clear_bit(n);
+
+        self.set_tag(tag);
+    }
+
+    /// Toggle bit `n` of the tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[track_caller]
+    pub fn toggle_tag_bit(&mut self, n: u32) {
+        let mut tag = self.tag();
+        tag.toggle_bit(n);
+
+        self.set_tag(tag);
+    }
+
+    /// Update the pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - `self` will have the same provenance as `ptr` after this call.
+    #[inline]
+    pub unsafe fn set_ptr(&mut self, ptr: NonNull<T>) {
+        *self = self.with_ptr(ptr);
+    }
+
+    /// Offset the pointer by `count` elements of `T`, keeping the tag.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as [`NonNull::add`] apply to the untagged pointer.
+    #[inline]
+    #[must_use]
+    pub unsafe fn add(self, count: usize) -> TagPtr<T> {
+        // SAFETY: The caller ensures that the offset is in bounds, and offsetting by whole
+        //         elements keeps the pointer aligned.
+        unsafe { TagPtr::new_unchecked(self.ptr().add(count), self.tag()) }
+    }
+
+    /// Offset the pointer backwards by `count` elements of `T`, keeping the tag.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as [`NonNull::sub`] apply to the untagged pointer.
+    #[inline]
+    #[must_use]
+    pub unsafe fn sub(self, count: usize) -> TagPtr<T> {
+        // SAFETY: The caller ensures that the offset is in bounds, and offsetting by whole
+        //         elements keeps the pointer aligned.
+        unsafe { TagPtr::new_unchecked(self.ptr().sub(count), self.tag()) }
+    }
+
+    /// Offset the pointer by `count` bytes, keeping the tag.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as [`NonNull::byte_add`] apply to the untagged pointer.
+    /// - The caller must ensure that the resulting pointer is properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn byte_add(self, count: usize) -> TagPtr<T> {
+        // SAFETY: The caller ensures that the offset is in bounds and that the result
+        //         is aligned.
+        unsafe { TagPtr::new_unchecked(self.ptr().byte_add(count), self.tag()) }
+    }
+
+    /// Offset the pointer by `count` elements of `T` using wrapping arithmetic, keeping the tag.
+    ///
+    /// Returns `None` if the resulting pointer is null.
+    #[inline]
+    #[must_use]
+    pub fn wrapping_add(self, count: usize) -> Option<TagPtr<T>> {
+        let ptr = NonNull::new(self.ptr().as_ptr().wrapping_add(count))?;
+
+        TagPtr::try_new(ptr, self.tag())
+    }
+
+    /// Offset the pointer backwards by `count` elements of `T` using wrapping arithmetic,
+    /// keeping the tag.
+    ///
+    /// Returns `None` if the resulting pointer is null.
+    #[inline]
+    #[must_use]
+    pub fn wrapping_sub(self, count: usize) -> Option<TagPtr<T>> {
+        let ptr = NonNull::new(self.ptr().as_ptr().wrapping_sub(count))?;
+
+        TagPtr::try_new(ptr, self.tag())
+    }
+
+    /// Offset the pointer by `count` bytes using wrapping arithmetic, keeping the tag.
+    ///
+    /// Returns `None` if the resulting pointer is null or not properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    pub fn wrapping_byte_add(self, count: usize) -> Option<TagPtr<T>> {
+        let ptr = NonNull::new(self.ptr().as_ptr().wrapping_byte_add(count))?;
+
+        TagPtr::try_new(ptr, self.tag())
+    }
+
+    /// Try to cast to a tagged pointer to a `U`, keeping the tag.
+    ///
+    /// Returns an error if the untagged pointer is not properly aligned for `U`, or if
+    /// the tag does not fit within the alignment bits of `U`.
+    #[inline]
+    pub fn try_cast<U>(self) -> Result<TagPtr<U>, CastError> {
+        let ptr = self.ptr().cast::<U>();
+
+        if !ptr.is_aligned() {
+            return Err(CastError::Misaligned);
+        }
+
+        let tag = Tag::new(self.tag().get()).ok_or(CastError::TagTooWide)?;
+
+        // SAFETY: We just checked that `ptr` is aligned for `U`.
+        Ok(unsafe { TagPtr::new_unchecked(ptr, tag) })
+    }
+
+    /// Cast to a tagged pointer to a `U`, keeping the tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the untagged pointer is not properly aligned for `U`, or if the tag
+    /// does not fit within the alignment bits of `U`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn cast<U>(self) -> TagPtr<U> {
+        match self.try_cast() {
+            Ok(ptr) => ptr,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Calculate the distance between two pointers in elements of `T`, ignoring their tags.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as [`NonNull::offset_from`] apply to the untagged pointers.
+    #[inline]
+    #[must_use]
+    pub unsafe fn offset_from(self, origin: TagPtr<T>) -> isize {
+        // SAFETY: The caller ensures that this is valid.
+        unsafe { self.ptr().offset_from(origin.ptr()) }
+    }
+
+    /// Returns an adapter whose `Debug` impl prints the raw tagged word, the untagged
+    /// address, and the tag in binary.
+    #[inline]
+    #[must_use]
+    pub fn debug_verbose(self) -> DebugVerbose<T> {
+        DebugVerbose { ptr: self }
+    }
+}
+
+/// A verbose `Debug` adapter for [`TagPtr`], see [`TagPtr::debug_verbose`].
+pub struct DebugVerbose<T> {
+    ptr: TagPtr<T>,
+}
+
+impl<T> fmt::Debug for DebugVerbose<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = T::ALIGN_BITS as usize + 2;
+
+        f.debug_struct("TagPtr")
+            .field("raw", &self.ptr.raw)
+            .field("ptr", &self.ptr.ptr())
+            .field(
+                "tag",
+                &format_args!("{:#0width$b}", self.ptr.tag().get(), width = width),
+            )
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for TagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TagPtr")
+            .field("ptr", &self.ptr())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+impl<T> fmt::Pointer for TagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ptr().fmt(f)
+    }
+}
+
+impl<T> Clone for TagPtr<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TagPtr<T> {}
+
+impl<T> PartialEq for TagPtr<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for TagPtr<T> {}
+
+impl<T> PartialOrd for TagPtr<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TagPtr<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> core::hash::Hash for TagPtr<T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+/// The error returned by [`TagPtr::try_cast`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastError {
+    /// The pointer is not properly aligned for the target type.
+    Misaligned,
+    /// The tag does not fit within the alignment bits of the target type.
+    TagTooWide,
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CastError::Misaligned => f.write_str("`ptr` is not aligned for the target type"),
+            CastError::TagTooWide => {
+                f.write_str("tag does not fit within alignment bits of the target type")
+            }
+        }
+    }
+}
+
+impl core::error::Error for CastError {}
+
+/// A [`TagPtr`] that can be shared between threads and updated atomically.
+///
+/// This is backed by an [`AtomicPtr`], so the provenance of stored pointers
+/// is preserved across loads and stores.
+#[repr(transparent)]
+pub struct AtomicTagPtr<T> {
+    raw: AtomicPtr<T>,
+}
+
+impl<T> AtomicTagPtr<T> {
+    /// Create a new atomic tagged pointer.
+    #[inline]
+    #[must_use]
+    pub const fn new(ptr: TagPtr<T>) -> AtomicTagPtr<T> {
+        AtomicTagPtr {
+            raw: AtomicPtr::new(ptr.raw.as_ptr()),
+        }
+    }
+
+    /// Get a mutable reference to the underlying tagged pointer.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self) -> &mut TagPtr<T> {
+        // SAFETY: `TagPtr<T>` is a transparent wrapper around `NonNull<T>`, which has the
+        //         same layout as `*mut T`. We only ever store non-null pointers that came
+        //         from a `TagPtr<T>`, so the pointee is always a valid `TagPtr<T>`.
+        unsafe { &mut *(self.raw.get_mut() as *mut *mut T).cast::<TagPtr<T>>() }
+    }
+
+    /// Consume the atomic and return the contained tagged pointer.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> TagPtr<T> {
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`.
+        unsafe { TagPtr::from_raw_unchecked(self.raw.into_inner()) }
+    }
+
+    /// Load the tagged pointer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn load(&self, order: Ordering) -> TagPtr<T> {
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`.
+        unsafe { TagPtr::from_raw_unchecked(self.raw.load(order)) }
+    }
+
+    /// Store a tagged pointer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Ordering::Acquire`] or [`Ordering::AcqRel`].
+    #[inline]
+    #[track_caller]
+    pub fn store(&self, ptr: TagPtr<T>, order: Ordering) {
+        self.raw.store(ptr.raw.as_ptr(), order);
+    }
+
+    /// Store a tagged pointer, returning the previous one.
+    #[inline]
+    pub fn swap(&self, ptr: TagPtr<T>, order: Ordering) -> TagPtr<T> {
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`.
+        unsafe { TagPtr::from_raw_unchecked(self.raw.swap(ptr.raw.as_ptr(), order)) }
+    }
+
+    /// Store `new` if the current value is `current`.
+    ///
+    /// Both the address and the tag must match. On success this returns the previous
+    /// value, and on failure it returns the value that was observed instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline]
+    #[track_caller]
+    pub fn compare_exchange(
+        &self,
+        current: TagPtr<T>,
+        new: TagPtr<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<TagPtr<T>, TagPtr<T>> {
+        let result =
+            self.raw
+                .compare_exchange(current.raw.as_ptr(), new.raw.as_ptr(), success, failure);
+
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`.
+        match result {
+            Ok(prev) => Ok(unsafe { TagPtr::from_raw_unchecked(prev) }),
+            Err(prev) => Err(unsafe { TagPtr::from_raw_unchecked(prev) }),
+        }
+    }
+
+    /// Store `new` if the current value is `current`, possibly failing spuriously.
+    ///
+    /// See [`AtomicTagPtr::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline]
+    #[track_caller]
+    pub fn compare_exchange_weak(
+        &self,
+        current: TagPtr<T>,
+        new: TagPtr<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<TagPtr<T>, TagPtr<T>> {
+        let result = self.raw.compare_exchange_weak(
+            current.raw.as_ptr(),
+            new.raw.as_ptr(),
+            success,
+            failure,
+        );
+
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`.
+        match result {
+            Ok(prev) => Ok(unsafe { TagPtr::from_raw_unchecked(prev) }),
+            Err(prev) => Err(unsafe { TagPtr::from_raw_unchecked(prev) }),
+        }
+    }
+
+    /// Bitwise or the tag with `tag`, leaving the address untouched, and return the
+    /// previous value.
+    #[inline]
+    pub fn fetch_or_tag(&self, tag: Tag<T>, order: Ordering) -> TagPtr<T> {
+        let prev = self.fetch_map_addr(order, |addr| addr | tag.get());
+
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`, and only the
+        //         tag bits were modified.
+        unsafe { TagPtr::from_raw_unchecked(prev) }
+    }
+
+    /// Bitwise and the tag with `tag`, leaving the address untouched, and return the
+    /// previous value.
+    #[inline]
+    pub fn fetch_and_tag(&self, tag: Tag<T>, order: Ordering) -> TagPtr<T> {
+        let prev = self.fetch_map_addr(order, |addr| addr & (tag.get() | T::PTR_MASK));
+
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`, and only the
+        //         tag bits were modified.
+        unsafe { TagPtr::from_raw_unchecked(prev) }
+    }
+
+    /// Bitwise xor the tag with `tag`, leaving the address untouched, and return the
+    /// previous value.
+    #[inline]
+    pub fn fetch_xor_tag(&self, tag: Tag<T>, order: Ordering) -> TagPtr<T> {
+        let prev = self.fetch_map_addr(order, |addr| addr ^ tag.get());
+
+        // SAFETY: We only ever store pointers that came from a `TagPtr<T>`, and only the
+        //         tag bits were modified.
+        unsafe { TagPtr::from_raw_unchecked(prev) }
+    }
+
+    /// Replace the tag with `new` if it is currently `current`, whatever the address is.
+    ///
+    /// On success this returns the previous value, and on failure it returns the value
+    /// that was observed instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline]
+    #[track_caller]
+    pub fn compare_exchange_tag(
+        &self,
+        current: Tag<T>,
+        new: Tag<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<TagPtr<T>, TagPtr<T>> {
+        self.fetch_update_tag(success, failure, |tag| (tag == current).then_some(new))
+    }
+
+    /// Repeatedly replace the tag with the result of `f`, leaving the address untouched,
+    /// until it succeeds or `f` returns `None`.
+    ///
+    /// This returns `Ok` with the previous value if `f` returned `Some`, and `Err` with
+    /// the last observed value otherwise. See [`AtomicPtr::fetch_update`] for how the
+    /// orderings are used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline]
+    #[track_caller]
+    pub fn fetch_update_tag(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: impl FnMut(Tag<T>) -> Option<Tag<T>>,
+    ) -> Result<TagPtr<T>, TagPtr<T>> {
+        let mut prev = self.load(fetch_order);
+
+        while let Some(tag) = f(prev.tag()) {
+            match self.compare_exchange_weak(prev, prev.with_tag(tag), set_order, fetch_order) {
+                Ok(prev) => return Ok(prev),
+                Err(next) => prev = next,
+            }
+        }
+
+        Err(prev)
+    }
+
+    /// Apply `f` to the stored address with a compare-exchange loop, returning the
+    /// previous pointer.
+    ///
+    /// `AtomicPtr`'s own bitwise operations need Rust 1.91, so this is used instead.
+    #[inline]
+    fn fetch_map_addr(&self, order: Ordering, mut f: impl FnMut(usize) -> usize) -> *mut T {
+        let failure = match order {
+            Ordering::Release => Ordering::Relaxed,
+            Ordering::AcqRel => Ordering::Acquire,
+            order => order,
+        };
+
+        let mut prev = self.raw.load(failure);
+
+        loop {
+            let next = strict::map_addr(prev, &mut f);
+
+            match self.raw.compare_exchange_weak(prev, next, order, failure) {
+                Ok(prev) => return prev,
+                Err(actual) => prev = actual,
+            }
+        }
+    }
+}
+
+impl<T> From<TagPtr<T>> for AtomicTagPtr<T> {
+    #[inline]
+    fn from(ptr: TagPtr<T>) -> Self {
+        AtomicTagPtr::new(ptr)
+    }
+}
+
+impl<T> fmt::Debug for AtomicTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.load(Ordering::Relaxed).fmt(f)
+    }
+}
+
+impl<T> fmt::Pointer for AtomicTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+/// A tagged pointer to a possibly unsized `T`, such as `[U]` or `dyn Trait`.
+///
+/// Only the address is tagged, any pointer metadata (a slice length, a vtable) is
+/// carried alongside untouched. Since the alignment of an unsized type generally
+/// isn't known statically, the tag bits are those of `A`, and the address must be
+/// aligned for `A`.
+#[repr(transparent)]
+pub struct UnsizedTagPtr<T: ?Sized, A> {
+    raw: NonNull<T>,
+    _marker: PhantomData<fn() -> A>,
+}
+
+/// A tagged pointer to a slice, using the alignment bits of its elements.
+pub type SliceTagPtr<T> = UnsizedTagPtr<[T], T>;
+
+impl<T: ?Sized, A> UnsizedTagPtr<T, A> {
+    /// Try to create a tagged pointer to a `T`.
+    ///
+    /// Returns `None` if the address of `ptr` is not aligned for `A`.
+    #[inline]
+    #[must_use]
+    pub fn try_new(ptr: NonNull<T>, tag: Tag<A>) -> Option<UnsizedTagPtr<T, A>> {
+        if ptr.cast::<A>().is_aligned() {
+            // SAFETY: Since the address of `ptr` is aligned and not null, the tag will never
+            //         conflict with the address, and additionally inserting the tag will never
+            //         cause the address to be null.
+            let raw = ptr.as_ptr().wrapping_byte_add(tag.get());
             let raw = unsafe { NonNull::new_unchecked(raw) };
 
-            Some(TagPtr { raw })
+            Some(UnsizedTagPtr {
+                raw,
+                _marker: PhantomData,
+            })
         } else {
             None
         }
@@ -53,12 +914,12 @@ impl<T> TagPtr<T> {
     ///
     /// # Safety
     ///
-    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The caller must ensure that the address of `ptr` is properly aligned for `A`.
     #[inline]
     #[must_use]
     #[track_caller]
-    pub unsafe fn new_unchecked(ptr: NonNull<T>, tag: Tag<T>) -> TagPtr<T> {
-        match TagPtr::<T>::try_new(ptr, tag) {
+    pub unsafe fn new_unchecked(ptr: NonNull<T>, tag: Tag<A>) -> UnsizedTagPtr<T, A> {
+        match UnsizedTagPtr::try_new(ptr, tag) {
             Some(ptr) => ptr,
             None if cfg!(debug_assertions) => panic!("`ptr` is not aligned"),
             // SAFETY: The caller ensures that `ptr` is aligned properly.
@@ -70,12 +931,12 @@ impl<T> TagPtr<T> {
     ///
     /// # Panics
     ///
-    /// Panics if `ptr` is not properly aligned for `T`.
+    /// Panics if the address of `ptr` is not properly aligned for `A`.
     #[inline]
     #[must_use]
     #[track_caller]
-    pub fn new(ptr: NonNull<T>, tag: Tag<T>) -> TagPtr<T> {
-        match TagPtr::<T>::try_new(ptr, tag) {
+    pub fn new(ptr: NonNull<T>, tag: Tag<A>) -> UnsizedTagPtr<T, A> {
+        match UnsizedTagPtr::try_new(ptr, tag) {
             Some(ptr) => ptr,
             None => panic!("`ptr` is not aligned"),
         }
@@ -84,132 +945,110 @@ impl<T> TagPtr<T> {
     /// Get the tag.
     #[inline]
     #[must_use]
-    pub fn tag(self) -> Tag<T> {
-        let tag = Strict::addr(self.raw.as_ptr()) & T::TAG_MASK;
+    pub fn tag(self) -> Tag<A> {
+        let tag = strict::addr(self.raw.as_ptr().cast::<u8>()) & A::TAG_MASK;
 
         Tag::new(tag).unwrap()
     }
 
-    /// Get the actual pointer.
-    ///
-    /// # Safety
-    ///
-    /// - The pointer is guaranteed to be properly aligned for `T`.
+    /// Get the actual pointer, with its metadata.
     #[inline]
     #[must_use]
     pub fn ptr(self) -> NonNull<T> {
-        let ptr = Strict::map_addr(self.raw.as_ptr(), |addr| addr & T::PTR_MASK);
-
-        unsafe {
-            // SAFETY: Creating a tagged pointer requires that the pointer without the tag
-            //         is properly aligned for `T` and non-null.
-            assert_unchecked(ptr.is_aligned());
+        let ptr = self.raw.as_ptr().wrapping_byte_sub(self.tag().get());
 
-            NonNull::new_unchecked(ptr)
-        }
+        // SAFETY: Creating a tagged pointer requires that the pointer without the tag
+        //         is non-null.
+        unsafe { NonNull::new_unchecked(ptr) }
     }
 
-    /// Create a new tagged pointer with the same address as `self`.
-    ///
-    /// # Safety
-    ///
-    /// - The resulting pointer has the same provenance as `self`.
+    /// Create a new tagged pointer with the same address and metadata as `self`.
     #[inline]
     #[must_use]
-    pub fn with_tag(self, tag: Tag<T>) -> TagPtr<T> {
+    pub fn with_tag(self, tag: Tag<A>) -> UnsizedTagPtr<T, A> {
         // SAFETY: We already know that `ptr` is valid, as it must be in order to construct
         //         a tagged pointer.
-        unsafe { TagPtr::new_unchecked(self.ptr(), tag) }
+        unsafe { UnsizedTagPtr::new_unchecked(self.ptr(), tag) }
     }
 
     /// Create a new tagged pointer with the same tag as `self`.
     ///
     /// # Safety
     ///
-    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The caller must ensure that the address of `ptr` is properly aligned for `A`.
     /// - The resulting pointer has the same provenance as `ptr`, not `self`.
     #[inline]
     #[must_use]
-    pub unsafe fn with_ptr(self, ptr: NonNull<T>) -> TagPtr<T> {
-        debug_assert!(ptr.is_aligned(), "`ptr` is not aligned");
+    pub unsafe fn with_ptr(self, ptr: NonNull<T>) -> UnsizedTagPtr<T, A> {
+        debug_assert!(ptr.cast::<A>().is_aligned(), "`ptr` is not aligned");
 
         // SAFETY: The caller ensures that `ptr` is valid.
-        unsafe { TagPtr::new_unchecked(ptr, self.tag()) }
+        unsafe { UnsizedTagPtr::new_unchecked(ptr, self.tag()) }
     }
 
     /// Update the tag.
     #[inline]
-    #[must_use]
-    pub fn set_tag(&mut self, tag: Tag<T>) {
+    pub fn set_tag(&mut self, tag: Tag<A>) {
         *self = self.with_tag(tag);
     }
 
     /// Update the pointer.
     ///
-    /// # Safety.
+    /// # Safety
     ///
-    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The caller must ensure that the address of `ptr` is properly aligned for `A`.
     /// - `self` will have the same provenance as `ptr` after this call.
     #[inline]
-    #[must_use]
     pub unsafe fn set_ptr(&mut self, ptr: NonNull<T>) {
         *self = self.with_ptr(ptr);
     }
 }
 
-impl<T> fmt::Debug for TagPtr<T> {
+impl<T> From<TagPtr<T>> for UnsizedTagPtr<T, T> {
+    #[inline]
+    fn from(ptr: TagPtr<T>) -> Self {
+        UnsizedTagPtr {
+            raw: ptr.raw,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A> fmt::Debug for UnsizedTagPtr<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("TagPtr")
+        f.debug_struct("UnsizedTagPtr")
             .field("ptr", &self.ptr())
             .field("tag", &self.tag())
             .finish()
     }
 }
 
-impl<T> fmt::Pointer for TagPtr<T> {
+impl<T: ?Sized, A> fmt::Pointer for UnsizedTagPtr<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.ptr().fmt(f)
     }
 }
 
-impl<T> Clone for TagPtr<T> {
+impl<T: ?Sized, A> Clone for UnsizedTagPtr<T, A> {
     #[inline]
     fn clone(&self) -> Self {
         *self
     }
 }
 
-impl<T> Copy for TagPtr<T> {}
+impl<T: ?Sized, A> Copy for UnsizedTagPtr<T, A> {}
 
-impl<T> PartialEq for TagPtr<T> {
+impl<T: ?Sized, A> PartialEq for UnsizedTagPtr<T, A> {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.raw == other.raw
-    }
-
-    #[inline]
-    fn ne(&self, other: &Self) -> bool {
-        self.raw != other.raw
-    }
-}
-
-impl<T> Eq for TagPtr<T> {}
-
-impl<T> PartialOrd for TagPtr<T> {
-    #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        Some(self.cmp(other))
+        // Two pointers with the same address but different metadata are different pointers.
+        core::ptr::eq(self.raw.as_ptr(), other.raw.as_ptr())
     }
 }
 
-impl<T> Ord for TagPtr<T> {
-    #[inline]
-    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        self.raw.cmp(&other.raw)
-    }
-}
+impl<T: ?Sized, A> Eq for UnsizedTagPtr<T, A> {}
 
-impl<T> core::hash::Hash for TagPtr<T> {
+impl<T: ?Sized, A> core::hash::Hash for UnsizedTagPtr<T, A> {
     #[inline]
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.raw.hash(state)
@@ -234,6 +1073,20 @@ impl<T> Tag<T> {
         _marker: PhantomData,
     };
 
+    /// The amount of bits available for tags.
+    pub const BITS: u32 = <T as HasLayout>::ALIGN_BITS;
+
+    /// Returns an iterator over every valid tag, in ascending order.
+    #[inline]
+    #[must_use]
+    pub const fn all() -> AllTags<T> {
+        AllTags {
+            start: 0,
+            end: T::TAG_MASK + 1,
+            _marker: PhantomData,
+        }
+    }
+
     /// Returns whether a provided tag is valid.
     #[inline]
     #[must_use]
@@ -256,7 +1109,29 @@ impl<T> Tag<T> {
         }
     }
 
+    /// Create a tag from a constant, failing compilation if it does not fit within
+    /// the alignment bits.
+    #[inline]
+    #[must_use]
+    pub const fn new_const<const TAG: usize>() -> Tag<T> {
+        const {
+            assert!(
+                Tag::<T>::is_valid(TAG),
+                "tag does not fit within alignment bits"
+            )
+        };
+
+        Tag {
+            tag: TAG,
+            _marker: PhantomData,
+        }
+    }
+
     /// Create a tag without checking that it fits in the alignment bits.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `tag` is valid, see [`Tag::is_valid`].
     #[inline]
     #[must_use]
     #[track_caller]
@@ -277,6 +1152,128 @@ impl<T> Tag<T> {
 
         self.tag
     }
+
+    /// Create a tag with only bit `n` set.
+    ///
+    /// Returns `None` if `n` is not one of the alignment bits.
+    #[inline]
+    #[must_use]
+    pub const fn bit(n: u32) -> Option<Tag<T>> {
+        if n < T::ALIGN_BITS {
+            Some(Tag {
+                tag: 1 << n,
+                _marker: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether bit `n` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub const fn get_bit(self, n: u32) -> bool {
+        match Tag::<T>::bit(n) {
+            Some(bit) => self.tag & bit.tag != 0,
+            None => panic!("bit index is out of range of the alignment bits"),
+        }
+    }
+
+    /// Set bit `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[track_caller]
+    pub const fn set_bit(&mut self, n: u32) {
+        match Tag::<T>::bit(n) {
+            Some(bit) => self.tag |= bit.tag,
+            None => panic!("bit index is out of range of the alignment bits"),
+        }
+    }
+
+    /// Clear bit `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[track_caller]
+    pub const fn clear_bit(&mut self, n: u32) {
+        match Tag::<T>::bit(n) {
+            Some(bit) => self.tag &= !bit.tag,
+            None => panic!("bit index is out of range of the alignment bits"),
+        }
+    }
+
+    /// Toggle bit `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not one of the alignment bits.
+    #[inline]
+    #[track_caller]
+    pub const fn toggle_bit(&mut self, n: u32) {
+        match Tag::<T>::bit(n) {
+            Some(bit) => self.tag ^= bit.tag,
+            None => panic!("bit index is out of range of the alignment bits"),
+        }
+    }
+
+    /// Add `rhs` to this tag, returning `None` if the result does not fit within the
+    /// alignment bits.
+    #[inline]
+    #[must_use]
+    pub const fn checked_add(self, rhs: usize) -> Option<Tag<T>> {
+        match self.tag.checked_add(rhs) {
+            Some(tag) => Tag::new(tag),
+            None => None,
+        }
+    }
+
+    /// Subtract `rhs` from this tag, returning `None` if the result underflows.
+    #[inline]
+    #[must_use]
+    pub const fn checked_sub(self, rhs: usize) -> Option<Tag<T>> {
+        match self.tag.checked_sub(rhs) {
+            Some(tag) => Tag::new(tag),
+            None => None,
+        }
+    }
+
+    /// Add `rhs` to this tag, wrapping around at the boundary of the alignment bits.
+    #[inline]
+    #[must_use]
+    pub const fn wrapping_add(self, rhs: usize) -> Tag<T> {
+        Tag {
+            tag: self.tag.wrapping_add(rhs) & T::TAG_MASK,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Subtract `rhs` from this tag, wrapping around at the boundary of the alignment bits.
+    #[inline]
+    #[must_use]
+    pub const fn wrapping_sub(self, rhs: usize) -> Tag<T> {
+        Tag {
+            tag: self.tag.wrapping_sub(rhs) & T::TAG_MASK,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Apply `f` to the integer representation of this tag, returning `None` if
+    /// the result does not fit within the alignment bits.
+    #[inline]
+    #[must_use]
+    pub fn map(self, f: impl FnOnce(usize) -> usize) -> Option<Tag<T>> {
+        Tag::new(f(self.get()))
+    }
 }
 
 impl<T> Default for Tag<T> {
@@ -286,6 +1283,61 @@ impl<T> Default for Tag<T> {
     }
 }
 
+/// The error returned when converting an integer that does not fit within the
+/// alignment bits into a [`Tag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvalidTagError(());
+
+impl fmt::Display for InvalidTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("tag does not fit within alignment bits")
+    }
+}
+
+impl core::error::Error for InvalidTagError {}
+
+macro_rules! tag_try_from {
+    ($($int:ty),* $(,)?) => {
+        $(
+            impl<T> TryFrom<$int> for Tag<T> {
+                type Error = InvalidTagError;
+
+                #[inline]
+                fn try_from(tag: $int) -> Result<Self, Self::Error> {
+                    usize::try_from(tag)
+                        .ok()
+                        .and_then(Tag::new)
+                        .ok_or(InvalidTagError(()))
+                }
+            }
+        )*
+    };
+}
+
+tag_try_from!(u8, u16, u32, u64, usize);
+
+/// `false` is `0` and `true` is `1`.
+///
+/// This fails to compile if `T` has no alignment bits.
+impl<T> From<bool> for Tag<T> {
+    #[inline]
+    fn from(tag: bool) -> Self {
+        const { assert!(T::TAGGING_ALLOWED, "tag does not fit within alignment bits") };
+
+        Tag {
+            tag: tag as usize,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> From<Tag<T>> for usize {
+    #[inline]
+    fn from(tag: Tag<T>) -> Self {
+        tag.get()
+    }
+}
+
 impl<T> Clone for Tag<T> {
     #[inline]
     fn clone(&self) -> Self {
@@ -300,11 +1352,6 @@ impl<T1, T2> PartialEq<Tag<T2>> for Tag<T1> {
     fn eq(&self, other: &Tag<T2>) -> bool {
         self.tag == other.tag
     }
-
-    #[inline]
-    fn ne(&self, other: &Tag<T2>) -> bool {
-        self.tag != other.tag
-    }
 }
 
 impl<T> PartialEq<usize> for Tag<T> {
@@ -312,11 +1359,6 @@ impl<T> PartialEq<usize> for Tag<T> {
     fn eq(&self, other: &usize) -> bool {
         self.tag == *other
     }
-
-    #[inline]
-    fn ne(&self, other: &usize) -> bool {
-        self.tag != *other
-    }
 }
 
 impl<T> Eq for Tag<T> {}
@@ -349,6 +1391,76 @@ impl<T> core::hash::Hash for Tag<T> {
     }
 }
 
+impl<T> BitOr for Tag<T> {
+    type Output = Tag<T>;
+
+    #[inline]
+    fn bitor(self, rhs: Tag<T>) -> Tag<T> {
+        Tag {
+            tag: self.tag | rhs.tag,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> BitOrAssign for Tag<T> {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Tag<T>) {
+        *self = *self | rhs;
+    }
+}
+
+impl<T> BitAnd for Tag<T> {
+    type Output = Tag<T>;
+
+    #[inline]
+    fn bitand(self, rhs: Tag<T>) -> Tag<T> {
+        Tag {
+            tag: self.tag & rhs.tag,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> BitAndAssign for Tag<T> {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Tag<T>) {
+        *self = *self & rhs;
+    }
+}
+
+impl<T> BitXor for Tag<T> {
+    type Output = Tag<T>;
+
+    #[inline]
+    fn bitxor(self, rhs: Tag<T>) -> Tag<T> {
+        Tag {
+            tag: self.tag ^ rhs.tag,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> BitXorAssign for Tag<T> {
+    #[inline]
+    fn bitxor_assign(&mut self, rhs: Tag<T>) {
+        *self = *self ^ rhs;
+    }
+}
+
+impl<T> Not for Tag<T> {
+    type Output = Tag<T>;
+
+    /// Flip every alignment bit, leaving the address bits clear.
+    #[inline]
+    fn not(self) -> Tag<T> {
+        Tag {
+            tag: !self.tag & T::TAG_MASK,
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<T> fmt::Debug for Tag<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("Tag").field(&self.tag).finish()
@@ -390,3 +1502,74 @@ impl<T> fmt::LowerExp for Tag<T> {
         self.tag.fmt(f)
     }
 }
+
+/// An iterator over every valid tag for a pointer to `T`.
+///
+/// This is returned by [`Tag::all`].
+pub struct AllTags<T> {
+    start: usize,
+    end: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Iterator for AllTags<T> {
+    type Item = Tag<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Tag<T>> {
+        if self.start < self.end {
+            let tag = self.start;
+            self.start += 1;
+
+            // SAFETY: Every value below `T::TAG_MASK + 1` is a valid tag.
+            Some(unsafe { Tag::new_unchecked(tag) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for AllTags<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Tag<T>> {
+        if self.start < self.end {
+            self.end -= 1;
+
+            // SAFETY: Every value below `T::TAG_MASK + 1` is a valid tag.
+            Some(unsafe { Tag::new_unchecked(self.end) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for AllTags<T> {}
+
+impl<T> FusedIterator for AllTags<T> {}
+
+impl<T> Clone for AllTags<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        AllTags {
+            start: self.start,
+            end: self.end,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for AllTags<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllTags")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
@@ -0,0 +1,337 @@
+use core::{
+    fmt,
+    hint::{assert_unchecked, unreachable_unchecked},
+    ptr::NonNull,
+};
+
+use crate::strict;
+
+/// A tagged pointer that stores a `BITS` wide tag in the low bits of a pointer.
+///
+/// Unlike [`TagPtr`], the amount of tag bits doesn't come from the alignment of `T`,
+/// but from the alignment the pointer is promised to have, which is useful for
+/// allocations that are over-aligned by hand. Every pointer stored in a
+/// `TagPtrN<T, BITS>` is aligned to `1 << BITS` bytes, and for `T`.
+///
+/// [`TagPtr`]: super::TagPtr
+#[repr(transparent)]
+pub struct TagPtrN<T, const BITS: u32> {
+    raw: NonNull<T>,
+}
+
+impl<T, const BITS: u32> TagPtrN<T, BITS> {
+    /// Returns whether `ptr` meets the alignment promise.
+    #[inline]
+    #[must_use]
+    pub fn is_aligned(ptr: NonNull<T>) -> bool {
+        ptr.is_aligned() && strict::addr(ptr.as_ptr()) & TagN::<BITS>::MAX.tag == 0
+    }
+
+    /// Try to create a tagged pointer to a `T`.
+    ///
+    /// Returns `None` if `ptr` is not aligned to `1 << BITS` bytes, or not aligned for `T`.
+    #[inline]
+    #[must_use]
+    pub fn try_new(ptr: NonNull<T>, tag: TagN<BITS>) -> Option<TagPtrN<T, BITS>> {
+        if TagPtrN::<T, BITS>::is_aligned(ptr) {
+            // SAFETY: Since `ptr` is aligned and not null, the tag will never conflict
+            //         with the address, and additionally inserting the tag will never
+            //         cause the address to be null.
+            let raw = strict::map_addr(ptr.as_ptr(), |addr| addr | tag.get());
+            let raw = unsafe { NonNull::new_unchecked(raw) };
+
+            Some(TagPtrN { raw })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tagged pointer to a `T` without checking the alignment promise.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is aligned to `1 << BITS` bytes, and
+    ///   properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn new_unchecked(ptr: NonNull<T>, tag: TagN<BITS>) -> TagPtrN<T, BITS> {
+        match TagPtrN::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None if cfg!(debug_assertions) => panic!("`ptr` is not aligned"),
+            // SAFETY: The caller ensures that `ptr` is aligned properly.
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Create a tagged pointer to a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not aligned to `1 << BITS` bytes, or not aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn new(ptr: NonNull<T>, tag: TagN<BITS>) -> TagPtrN<T, BITS> {
+        match TagPtrN::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None => panic!("`ptr` is not aligned"),
+        }
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(self) -> TagN<BITS> {
+        let tag = strict::addr(self.raw.as_ptr()) & TagN::<BITS>::MAX.tag;
+
+        TagN::new(tag).unwrap()
+    }
+
+    /// Get the actual pointer.
+    #[inline]
+    #[must_use]
+    pub fn ptr(self) -> NonNull<T> {
+        let ptr = strict::map_addr(self.raw.as_ptr(), |addr| addr & !TagN::<BITS>::MAX.tag);
+
+        unsafe {
+            // SAFETY: Creating a tagged pointer requires that the pointer without the tag
+            //         is properly aligned for `T` and non-null.
+            assert_unchecked(ptr.is_aligned());
+
+            NonNull::new_unchecked(ptr)
+        }
+    }
+
+    /// Create a new tagged pointer with the same address as `self`.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: TagN<BITS>) -> TagPtrN<T, BITS> {
+        // SAFETY: We already know that `ptr` is valid, as it must be in order to construct
+        //         a tagged pointer.
+        unsafe { TagPtrN::new_unchecked(self.ptr(), tag) }
+    }
+
+    /// Create a new tagged pointer with the same tag as `self`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is aligned to `1 << BITS` bytes, and
+    ///   properly aligned for `T`.
+    /// - The resulting pointer has the same provenance as `ptr`, not `self`.
+    #[inline]
+    #[must_use]
+    pub unsafe fn with_ptr(self, ptr: NonNull<T>) -> TagPtrN<T, BITS> {
+        // SAFETY: The caller ensures that `ptr` is valid.
+        unsafe { TagPtrN::new_unchecked(ptr, self.tag()) }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: TagN<BITS>) {
+        *self = self.with_tag(tag);
+    }
+
+    /// Update the pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is aligned to `1 << BITS` bytes, and
+    ///   properly aligned for `T`.
+    /// - `self` will have the same provenance as `ptr` after this call.
+    #[inline]
+    pub unsafe fn set_ptr(&mut self, ptr: NonNull<T>) {
+        *self = self.with_ptr(ptr);
+    }
+}
+
+impl<T, const BITS: u32> fmt::Debug for TagPtrN<T, BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TagPtrN")
+            .field("ptr", &self.ptr())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+impl<T, const BITS: u32> fmt::Pointer for TagPtrN<T, BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ptr().fmt(f)
+    }
+}
+
+impl<T, const BITS: u32> Clone for TagPtrN<T, BITS> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const BITS: u32> Copy for TagPtrN<T, BITS> {}
+
+impl<T, const BITS: u32> PartialEq for TagPtrN<T, BITS> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T, const BITS: u32> Eq for TagPtrN<T, BITS> {}
+
+impl<T, const BITS: u32> PartialOrd for TagPtrN<T, BITS> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, const BITS: u32> Ord for TagPtrN<T, BITS> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T, const BITS: u32> core::hash::Hash for TagPtrN<T, BITS> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+/// A type that will always fit in the low `BITS` bits of a pointer.
+#[repr(transparent)]
+pub struct TagN<const BITS: u32> {
+    tag: usize,
+}
+
+impl<const BITS: u32> TagN<BITS> {
+    pub const MIN: TagN<BITS> = TagN { tag: 0 };
+
+    pub const MAX: TagN<BITS> = TagN {
+        tag: {
+            assert!(BITS < usize::BITS, "too many tag bits");
+
+            (1 << BITS) - 1
+        },
+    };
+
+    /// The amount of bits available for tags.
+    pub const BITS: u32 = BITS;
+
+    /// Returns whether a provided tag is valid.
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(tag: usize) -> bool {
+        tag <= TagN::<BITS>::MAX.tag
+    }
+
+    /// Try to create a tag, returning `None` if the tag does not fit within `BITS` bits.
+    #[inline]
+    #[must_use]
+    pub const fn new(tag: usize) -> Option<TagN<BITS>> {
+        if TagN::<BITS>::is_valid(tag) {
+            Some(TagN { tag })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tag without checking that it fits in `BITS` bits.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `tag` is valid, see [`TagN::is_valid`].
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub const unsafe fn new_unchecked(tag: usize) -> TagN<BITS> {
+        match TagN::<BITS>::new(tag) {
+            Some(tag) => tag,
+            None if cfg!(debug_assertions) => panic!("tag does not fit within the tag bits"),
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Get the integer representation of this tag.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> usize {
+        // SAFETY: Creating a tag requires that it always meets `Self::is_valid`.
+        unsafe { assert_unchecked(TagN::<BITS>::is_valid(self.tag)) };
+
+        self.tag
+    }
+}
+
+impl<const BITS: u32> Default for TagN<BITS> {
+    #[inline]
+    fn default() -> Self {
+        Self::MIN
+    }
+}
+
+impl<const BITS: u32> Clone for TagN<BITS> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const BITS: u32> Copy for TagN<BITS> {}
+
+impl<const BITS: u32> PartialEq for TagN<BITS> {
+    #[inline]
+    fn eq(&self, other: &TagN<BITS>) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl<const BITS: u32> PartialEq<usize> for TagN<BITS> {
+    #[inline]
+    fn eq(&self, other: &usize) -> bool {
+        self.tag == *other
+    }
+}
+
+impl<const BITS: u32> Eq for TagN<BITS> {}
+
+impl<const BITS: u32> PartialOrd for TagN<BITS> {
+    #[inline]
+    fn partial_cmp(&self, other: &TagN<BITS>) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const BITS: u32> PartialOrd<usize> for TagN<BITS> {
+    #[inline]
+    fn partial_cmp(&self, other: &usize) -> Option<core::cmp::Ordering> {
+        Some(self.tag.cmp(other))
+    }
+}
+
+impl<const BITS: u32> Ord for TagN<BITS> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.tag.cmp(&other.tag)
+    }
+}
+
+impl<const BITS: u32> core::hash::Hash for TagN<BITS> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.tag.hash(state)
+    }
+}
+
+impl<const BITS: u32> fmt::Debug for TagN<BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TagN").field(&self.tag).finish()
+    }
+}
+
+impl<const BITS: u32> fmt::Display for TagN<BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tag.fmt(f)
+    }
+}
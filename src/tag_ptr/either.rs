@@ -0,0 +1,199 @@
+use core::{fmt, marker::PhantomData, ptr::NonNull};
+
+use crate::{layout::HasLayout, strict};
+
+/// The tag bit used to mark a [`TaggedEither`] as holding the right pointer.
+const RIGHT: usize = 1;
+
+/// Either a pointer to an `A` or a pointer to a `B`, in a single word.
+///
+/// The lowest alignment bit says which of the two is stored, so both `A` and `B`
+/// need at least one alignment bit. This is checked at compile time.
+#[repr(transparent)]
+pub struct TaggedEither<A, B> {
+    raw: NonNull<u8>,
+    _marker: PhantomData<(NonNull<A>, NonNull<B>)>,
+}
+
+impl<A, B> TaggedEither<A, B> {
+    /// Fails compilation if either `A` or `B` doesn't have an alignment bit to spare.
+    const BIT_AVAILABLE: () = assert!(
+        A::TAGGING_ALLOWED && B::TAGGING_ALLOWED,
+        "both pointee types need at least one alignment bit"
+    );
+
+    /// Try to create a tagged either holding a pointer to an `A`.
+    ///
+    /// Returns `None` if `ptr` is not aligned for `A`.
+    #[inline]
+    #[must_use]
+    pub fn try_left(ptr: NonNull<A>) -> Option<TaggedEither<A, B>> {
+        let () = Self::BIT_AVAILABLE;
+
+        if ptr.is_aligned() {
+            Some(TaggedEither {
+                raw: ptr.cast(),
+                _marker: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Try to create a tagged either holding a pointer to a `B`.
+    ///
+    /// Returns `None` if `ptr` is not aligned for `B`.
+    #[inline]
+    #[must_use]
+    pub fn try_right(ptr: NonNull<B>) -> Option<TaggedEither<A, B>> {
+        let () = Self::BIT_AVAILABLE;
+
+        if ptr.is_aligned() {
+            // SAFETY: Since `ptr` is aligned and not null, setting the lowest bit
+            //         never conflicts with the address or makes it null.
+            let raw = strict::map_addr(ptr.as_ptr().cast::<u8>(), |addr| addr | RIGHT);
+            let raw = unsafe { NonNull::new_unchecked(raw) };
+
+            Some(TaggedEither {
+                raw,
+                _marker: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tagged either holding a pointer to an `A`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not properly aligned for `A`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn left(ptr: NonNull<A>) -> TaggedEither<A, B> {
+        match TaggedEither::try_left(ptr) {
+            Some(either) => either,
+            None => panic!("`ptr` is not aligned"),
+        }
+    }
+
+    /// Create a tagged either holding a pointer to a `B`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not properly aligned for `B`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn right(ptr: NonNull<B>) -> TaggedEither<A, B> {
+        match TaggedEither::try_right(ptr) {
+            Some(either) => either,
+            None => panic!("`ptr` is not aligned"),
+        }
+    }
+
+    /// Returns whether this holds a pointer to an `A`.
+    #[inline]
+    #[must_use]
+    pub fn is_left(self) -> bool {
+        strict::addr(self.raw.as_ptr()) & RIGHT == 0
+    }
+
+    /// Returns whether this holds a pointer to a `B`.
+    #[inline]
+    #[must_use]
+    pub fn is_right(self) -> bool {
+        !self.is_left()
+    }
+
+    /// Get the pointer to an `A`, if that is what this holds.
+    #[inline]
+    #[must_use]
+    pub fn as_left(self) -> Option<NonNull<A>> {
+        if self.is_left() {
+            Some(self.raw.cast())
+        } else {
+            None
+        }
+    }
+
+    /// Get the pointer to a `B`, if that is what this holds.
+    #[inline]
+    #[must_use]
+    pub fn as_right(self) -> Option<NonNull<B>> {
+        if self.is_right() {
+            let ptr = strict::map_addr(self.raw.as_ptr(), |addr| addr & !RIGHT);
+
+            // SAFETY: Creating a tagged either requires that the pointer without the tag
+            //         is non-null.
+            Some(unsafe { NonNull::new_unchecked(ptr).cast() })
+        } else {
+            None
+        }
+    }
+
+    /// Call `left` or `right` with whichever pointer this holds.
+    #[inline]
+    pub fn map_either<R>(
+        self,
+        left: impl FnOnce(NonNull<A>) -> R,
+        right: impl FnOnce(NonNull<B>) -> R,
+    ) -> R {
+        match self.as_left() {
+            Some(ptr) => left(ptr),
+            None => right(self.as_right().unwrap()),
+        }
+    }
+
+    /// Swap the sides, so that a left pointer becomes a right pointer and vice versa.
+    #[inline]
+    #[must_use]
+    pub fn flip(self) -> TaggedEither<B, A> {
+        let raw = strict::map_addr(self.raw.as_ptr(), |addr| addr ^ RIGHT);
+
+        // SAFETY: Creating a tagged either requires that the pointer without the tag
+        //         is non-null, so toggling the tag keeps it non-null.
+        TaggedEither {
+            raw: unsafe { NonNull::new_unchecked(raw) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A, B> fmt::Debug for TaggedEither<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_left() {
+            Some(ptr) => f.debug_tuple("Left").field(&ptr).finish(),
+            None => f
+                .debug_tuple("Right")
+                .field(&self.as_right().unwrap())
+                .finish(),
+        }
+    }
+}
+
+impl<A, B> Clone for TaggedEither<A, B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A, B> Copy for TaggedEither<A, B> {}
+
+impl<A, B> PartialEq for TaggedEither<A, B> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<A, B> Eq for TaggedEither<A, B> {}
+
+impl<A, B> core::hash::Hash for TaggedEither<A, B> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
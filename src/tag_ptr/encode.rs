@@ -0,0 +1,73 @@
+use crate::layout::HasLayout;
+
+use super::Tag;
+
+/// A type that can be stored in the alignment bits of a pointer to `T`.
+///
+/// This is mainly intended for fieldless enums and small sets of flags, so that
+/// tags don't need to be passed around as bare integers.
+///
+/// Whether [`TagEncode::MAX`] fits within the alignment bits of `T` is checked at
+/// compile time whenever the encoding is used through [`TagPtr::tag_as`] or
+/// [`TagPtr::with_tag_as`].
+///
+/// With the `derive` feature, this can be derived for fieldless enums with
+/// `#[derive(PointerTag)]`.
+///
+/// [`TagPtr::tag_as`]: super::TagPtr::tag_as
+/// [`TagPtr::with_tag_as`]: super::TagPtr::with_tag_as
+pub trait TagEncode<T>: Sized {
+    /// The largest integer representation that [`TagEncode::encode`] produces.
+    const MAX: usize;
+
+    /// Fails compilation if [`TagEncode::MAX`] doesn't fit in the alignment bits of `T`.
+    #[doc(hidden)]
+    const FITS: () = assert!(
+        Self::MAX <= T::TAG_MASK,
+        "the tag encoding does not fit within the alignment bits"
+    );
+
+    /// Encode `self` as a tag.
+    #[must_use]
+    fn encode(self) -> Tag<T>;
+
+    /// Decode a tag produced by [`TagEncode::encode`].
+    ///
+    /// Implementations may panic if `tag` was not produced by [`TagEncode::encode`].
+    #[must_use]
+    fn decode(tag: Tag<T>) -> Self;
+}
+
+impl<T> TagEncode<T> for Tag<T> {
+    const MAX: usize = T::TAG_MASK;
+
+    #[inline]
+    fn encode(self) -> Tag<T> {
+        self
+    }
+
+    #[inline]
+    fn decode(tag: Tag<T>) -> Self {
+        tag
+    }
+}
+
+impl<T> TagEncode<T> for bool {
+    const MAX: usize = 1;
+
+    #[inline]
+    #[track_caller]
+    fn encode(self) -> Tag<T> {
+        Tag::new(self as usize).expect("the tag encoding does not fit within the alignment bits")
+    }
+
+    #[inline]
+    #[track_caller]
+    fn decode(tag: Tag<T>) -> Self {
+        match tag.get() {
+            0 => false,
+            1 => true,
+            _ => panic!("invalid tag for `bool`"),
+        }
+    }
+}
@@ -0,0 +1,723 @@
+use core::{
+    fmt,
+    hint::{assert_unchecked, unreachable_unchecked},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+use crate::{layout::HasLayout, strict};
+
+use super::Tag;
+
+/// The amount of upper address bits that [`HighTagPtr`] stores its tag in.
+pub const HIGH_BITS: u32 = 16;
+
+/// The amount of bits actually used for addresses by [`HighTagPtr`].
+const ADDR_BITS: u32 = usize::BITS - HIGH_BITS;
+
+/// A bitmask of the address bits.
+const ADDR_MASK: usize = usize::MAX >> HIGH_BITS;
+
+/// Sign extend the lower [`ADDR_BITS`] of `addr`, restoring a canonical address.
+#[inline]
+#[must_use]
+const fn canonicalize(addr: usize) -> usize {
+    ((addr << HIGH_BITS) as isize >> HIGH_BITS) as usize
+}
+
+/// A tagged pointer that stores a tag in the unused upper bits of a pointer.
+///
+/// On x86_64 and aarch64 only the lower 48 bits of an address are significant,
+/// and the upper bits are a sign extension of bit 47. This stores a tag in those
+/// upper bits instead of in the alignment bits, so it works for pointers to types
+/// of any alignment, including `u8`.
+///
+/// Pointers that aren't canonical 48-bit addresses (for instance under 5-level
+/// paging) are rejected by the constructors.
+#[repr(transparent)]
+pub struct HighTagPtr<T> {
+    raw: NonNull<T>,
+}
+
+impl<T> HighTagPtr<T> {
+    /// Create a dangling tagged pointer to a `T`.
+    #[inline]
+    #[must_use]
+    pub const fn dangling(tag: HighTag) -> HighTagPtr<T> {
+        // SAFETY: Since `T::ALIGN` is never zero, `raw` is never null.
+        let raw = strict::without_provenance_mut(T::ALIGN.get() | (tag.get() << ADDR_BITS));
+        let raw = unsafe { NonNull::new_unchecked(raw) };
+
+        HighTagPtr { raw }
+    }
+
+    /// Try to create a tagged pointer to a `T`.
+    ///
+    /// Returns `None` if `ptr` is not a canonical 48-bit address.
+    #[inline]
+    #[must_use]
+    pub fn try_new(ptr: NonNull<T>, tag: HighTag) -> Option<HighTagPtr<T>> {
+        let addr = strict::addr(ptr.as_ptr());
+
+        if canonicalize(addr) == addr {
+            // SAFETY: Since `ptr` is canonical and not null, its lower bits are never all
+            //         zero, so the result is never null.
+            let raw = strict::map_addr(ptr.as_ptr(), |addr| {
+                (addr & ADDR_MASK) | (tag.get() << ADDR_BITS)
+            });
+            let raw = unsafe { NonNull::new_unchecked(raw) };
+
+            Some(HighTagPtr { raw })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tagged pointer to a `T` without safety checks.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is a canonical 48-bit address.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn new_unchecked(ptr: NonNull<T>, tag: HighTag) -> HighTagPtr<T> {
+        match HighTagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None if cfg!(debug_assertions) => panic!("`ptr` is not canonical"),
+            // SAFETY: The caller ensures that `ptr` is canonical.
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Create a tagged pointer to a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not a canonical 48-bit address.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn new(ptr: NonNull<T>, tag: HighTag) -> HighTagPtr<T> {
+        match HighTagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None => panic!("`ptr` is not canonical"),
+        }
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(self) -> HighTag {
+        let tag = strict::addr(self.raw.as_ptr()) >> ADDR_BITS;
+
+        HighTag::new(tag).unwrap()
+    }
+
+    /// Get the actual pointer.
+    #[inline]
+    #[must_use]
+    pub fn ptr(self) -> NonNull<T> {
+        let ptr = strict::map_addr(self.raw.as_ptr(), canonicalize);
+
+        // SAFETY: Creating a tagged pointer requires that the pointer without the tag
+        //         is canonical and non-null.
+        unsafe { NonNull::new_unchecked(ptr) }
+    }
+
+    /// Create a new tagged pointer with the same address as `self`.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: HighTag) -> HighTagPtr<T> {
+        // SAFETY: We already know that `ptr` is valid, as it must be in order to construct
+        //         a tagged pointer.
+        unsafe { HighTagPtr::new_unchecked(self.ptr(), tag) }
+    }
+
+    /// Create a new tagged pointer with the same tag as `self`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is a canonical 48-bit address.
+    /// - The resulting pointer has the same provenance as `ptr`, not `self`.
+    #[inline]
+    #[must_use]
+    pub unsafe fn with_ptr(self, ptr: NonNull<T>) -> HighTagPtr<T> {
+        // SAFETY: The caller ensures that `ptr` is valid.
+        unsafe { HighTagPtr::new_unchecked(ptr, self.tag()) }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: HighTag) {
+        *self = self.with_tag(tag);
+    }
+
+    /// Update the pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is a canonical 48-bit address.
+    /// - `self` will have the same provenance as `ptr` after this call.
+    #[inline]
+    pub unsafe fn set_ptr(&mut self, ptr: NonNull<T>) {
+        *self = self.with_ptr(ptr);
+    }
+}
+
+impl<T> fmt::Debug for HighTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HighTagPtr")
+            .field("ptr", &self.ptr())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+impl<T> fmt::Pointer for HighTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ptr().fmt(f)
+    }
+}
+
+impl<T> Clone for HighTagPtr<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for HighTagPtr<T> {}
+
+impl<T> PartialEq for HighTagPtr<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for HighTagPtr<T> {}
+
+impl<T> PartialOrd for HighTagPtr<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for HighTagPtr<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> core::hash::Hash for HighTagPtr<T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+/// A type that will always fit in the upper [`HIGH_BITS`] of a pointer.
+#[repr(transparent)]
+pub struct HighTag {
+    tag: usize,
+}
+
+impl HighTag {
+    pub const MIN: HighTag = HighTag { tag: 0 };
+
+    pub const MAX: HighTag = HighTag {
+        tag: usize::MAX >> ADDR_BITS,
+    };
+
+    /// Returns whether a provided tag is valid.
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(tag: usize) -> bool {
+        tag <= HighTag::MAX.tag
+    }
+
+    /// Try to create a tag, returning `None` if the tag
+    /// does not fit within the upper bits.
+    #[inline]
+    #[must_use]
+    pub const fn new(tag: usize) -> Option<HighTag> {
+        if HighTag::is_valid(tag) {
+            Some(HighTag { tag })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tag without checking that it fits in the upper bits.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `tag` is valid, see [`HighTag::is_valid`].
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub const unsafe fn new_unchecked(tag: usize) -> HighTag {
+        match HighTag::new(tag) {
+            Some(tag) => tag,
+            None if cfg!(debug_assertions) => panic!("tag does not fit within upper bits"),
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Get the integer representation of this tag.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> usize {
+        // SAFETY: Creating a tag requires that it always meets `Self::is_valid`.
+        unsafe { assert_unchecked(HighTag::is_valid(self.tag)) };
+
+        self.tag
+    }
+}
+
+impl Default for HighTag {
+    #[inline]
+    fn default() -> Self {
+        Self::MIN
+    }
+}
+
+impl Clone for HighTag {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Copy for HighTag {}
+
+impl PartialEq for HighTag {
+    #[inline]
+    fn eq(&self, other: &HighTag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl PartialEq<usize> for HighTag {
+    #[inline]
+    fn eq(&self, other: &usize) -> bool {
+        self.tag == *other
+    }
+}
+
+impl Eq for HighTag {}
+
+impl PartialOrd for HighTag {
+    #[inline]
+    fn partial_cmp(&self, other: &HighTag) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialOrd<usize> for HighTag {
+    #[inline]
+    fn partial_cmp(&self, other: &usize) -> Option<core::cmp::Ordering> {
+        Some(self.tag.cmp(other))
+    }
+}
+
+impl Ord for HighTag {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.tag.cmp(&other.tag)
+    }
+}
+
+impl core::hash::Hash for HighTag {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.tag.hash(state)
+    }
+}
+
+impl fmt::Debug for HighTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HighTag").field(&self.tag).finish()
+    }
+}
+
+impl fmt::Display for HighTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tag.fmt(f)
+    }
+}
+
+/// A tagged pointer that stores a tag in both the alignment bits and the unused
+/// upper bits of a pointer.
+///
+/// The low bits of a [`WideTag`] go in the alignment bits, and the rest go in the
+/// upper [`HIGH_BITS`], which gives pointers to an 8-aligned type a 19 bit tag. The
+/// split is an implementation detail, the tag is always handled as one integer.
+///
+/// Like [`HighTagPtr`], pointers that aren't canonical 48-bit addresses are rejected
+/// by the constructors.
+#[repr(transparent)]
+pub struct WideTagPtr<T> {
+    raw: NonNull<T>,
+}
+
+impl<T> WideTagPtr<T> {
+    /// Create a dangling tagged pointer to a `T`.
+    #[inline]
+    #[must_use]
+    pub const fn dangling(tag: WideTag<T>) -> WideTagPtr<T> {
+        // SAFETY: Since `T::ALIGN` is never zero, `raw` is never null.
+        let raw = strict::without_provenance_mut(T::ALIGN.get() | tag.split());
+        let raw = unsafe { NonNull::new_unchecked(raw) };
+
+        WideTagPtr { raw }
+    }
+
+    /// Try to create a tagged pointer to a `T`.
+    ///
+    /// Returns `None` if `ptr` is not aligned for `T`, or is not a canonical 48-bit address.
+    #[inline]
+    #[must_use]
+    pub fn try_new(ptr: NonNull<T>, tag: WideTag<T>) -> Option<WideTagPtr<T>> {
+        let addr = strict::addr(ptr.as_ptr());
+
+        if ptr.is_aligned() && canonicalize(addr) == addr {
+            // SAFETY: Since `ptr` is aligned, canonical and not null, its lower address bits are
+            //         never all zero and never conflict with the tag, so the result is never null.
+            let raw = strict::map_addr(ptr.as_ptr(), |addr| (addr & ADDR_MASK) | tag.split());
+            let raw = unsafe { NonNull::new_unchecked(raw) };
+
+            Some(WideTagPtr { raw })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tagged pointer to a `T` without safety checks.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The caller must ensure that `ptr` is a canonical 48-bit address.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn new_unchecked(ptr: NonNull<T>, tag: WideTag<T>) -> WideTagPtr<T> {
+        match WideTagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None if cfg!(debug_assertions) => panic!("`ptr` is not aligned or not canonical"),
+            // SAFETY: The caller ensures that `ptr` is aligned and canonical.
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Create a tagged pointer to a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not properly aligned for `T`, or is not a canonical 48-bit address.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn new(ptr: NonNull<T>, tag: WideTag<T>) -> WideTagPtr<T> {
+        match WideTagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None => panic!("`ptr` is not aligned or not canonical"),
+        }
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(self) -> WideTag<T> {
+        WideTag::join(strict::addr(self.raw.as_ptr()))
+    }
+
+    /// Get the actual pointer.
+    #[inline]
+    #[must_use]
+    pub fn ptr(self) -> NonNull<T> {
+        let ptr = strict::map_addr(self.raw.as_ptr(), |addr| canonicalize(addr) & T::PTR_MASK);
+
+        unsafe {
+            // SAFETY: Creating a tagged pointer requires that the pointer without the tag
+            //         is properly aligned for `T`, canonical and non-null.
+            assert_unchecked(ptr.is_aligned());
+
+            NonNull::new_unchecked(ptr)
+        }
+    }
+
+    /// Create a new tagged pointer with the same address as `self`.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: WideTag<T>) -> WideTagPtr<T> {
+        // SAFETY: We already know that `ptr` is valid, as it must be in order to construct
+        //         a tagged pointer.
+        unsafe { WideTagPtr::new_unchecked(self.ptr(), tag) }
+    }
+
+    /// Create a new tagged pointer with the same tag as `self`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The caller must ensure that `ptr` is a canonical 48-bit address.
+    /// - The resulting pointer has the same provenance as `ptr`, not `self`.
+    #[inline]
+    #[must_use]
+    pub unsafe fn with_ptr(self, ptr: NonNull<T>) -> WideTagPtr<T> {
+        // SAFETY: The caller ensures that `ptr` is valid.
+        unsafe { WideTagPtr::new_unchecked(ptr, self.tag()) }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: WideTag<T>) {
+        *self = self.with_tag(tag);
+    }
+
+    /// Update the pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The caller must ensure that `ptr` is a canonical 48-bit address.
+    /// - `self` will have the same provenance as `ptr` after this call.
+    #[inline]
+    pub unsafe fn set_ptr(&mut self, ptr: NonNull<T>) {
+        *self = self.with_ptr(ptr);
+    }
+}
+
+impl<T> fmt::Debug for WideTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WideTagPtr")
+            .field("ptr", &self.ptr())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+impl<T> fmt::Pointer for WideTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ptr().fmt(f)
+    }
+}
+
+impl<T> Clone for WideTagPtr<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WideTagPtr<T> {}
+
+impl<T> PartialEq for WideTagPtr<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for WideTagPtr<T> {}
+
+impl<T> PartialOrd for WideTagPtr<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for WideTagPtr<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> core::hash::Hash for WideTagPtr<T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+/// A type that will always fit in the alignment bits and upper [`HIGH_BITS`] of a
+/// pointer to `T`.
+#[repr(transparent)]
+pub struct WideTag<T> {
+    tag: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> WideTag<T> {
+    pub const MIN: WideTag<T> = WideTag {
+        tag: 0,
+        _marker: PhantomData,
+    };
+
+    pub const MAX: WideTag<T> = WideTag {
+        tag: usize::MAX >> (usize::BITS - WideTag::<T>::BITS),
+        _marker: PhantomData,
+    };
+
+    /// The amount of bits available for tags.
+    pub const BITS: u32 = T::ALIGN_BITS + HIGH_BITS;
+
+    /// Returns whether a provided tag is valid.
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(tag: usize) -> bool {
+        tag <= WideTag::<T>::MAX.tag
+    }
+
+    /// Try to create a tag, returning `None` if the tag does not fit within
+    /// the alignment bits and upper bits.
+    #[inline]
+    #[must_use]
+    pub const fn new(tag: usize) -> Option<WideTag<T>> {
+        if WideTag::<T>::is_valid(tag) {
+            Some(WideTag {
+                tag,
+                _marker: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tag without checking that it fits in the alignment bits and upper bits.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `tag` is valid, see [`WideTag::is_valid`].
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub const unsafe fn new_unchecked(tag: usize) -> WideTag<T> {
+        match WideTag::<T>::new(tag) {
+            Some(tag) => tag,
+            None if cfg!(debug_assertions) => {
+                panic!("tag does not fit within alignment bits and upper bits")
+            }
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Get the integer representation of this tag.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> usize {
+        // SAFETY: Creating a tag requires that it always meets `Self::is_valid`.
+        unsafe { assert_unchecked(WideTag::<T>::is_valid(self.tag)) };
+
+        self.tag
+    }
+
+    /// Split the tag into the bits it occupies in a pointer.
+    #[inline]
+    #[must_use]
+    const fn split(self) -> usize {
+        let low = self.get() & T::TAG_MASK;
+        let high = self.get() >> T::ALIGN_BITS;
+
+        low | (high << ADDR_BITS)
+    }
+
+    /// Join the tag bits of a pointer's address into a tag.
+    #[inline]
+    #[must_use]
+    fn join(addr: usize) -> WideTag<T> {
+        let low = addr & T::TAG_MASK;
+        let high = addr >> ADDR_BITS;
+
+        WideTag::new(low | (high << T::ALIGN_BITS)).unwrap()
+    }
+}
+
+impl<T> From<Tag<T>> for WideTag<T> {
+    #[inline]
+    fn from(tag: Tag<T>) -> Self {
+        WideTag {
+            tag: tag.get(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for WideTag<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::MIN
+    }
+}
+
+impl<T> Clone for WideTag<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WideTag<T> {}
+
+impl<T> PartialEq for WideTag<T> {
+    #[inline]
+    fn eq(&self, other: &WideTag<T>) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl<T> PartialEq<usize> for WideTag<T> {
+    #[inline]
+    fn eq(&self, other: &usize) -> bool {
+        self.tag == *other
+    }
+}
+
+impl<T> Eq for WideTag<T> {}
+
+impl<T> PartialOrd for WideTag<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &WideTag<T>) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialOrd<usize> for WideTag<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &usize) -> Option<core::cmp::Ordering> {
+        Some(self.tag.cmp(other))
+    }
+}
+
+impl<T> Ord for WideTag<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.tag.cmp(&other.tag)
+    }
+}
+
+impl<T> core::hash::Hash for WideTag<T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.tag.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for WideTag<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WideTag").field(&self.tag).finish()
+    }
+}
+
+impl<T> fmt::Display for WideTag<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tag.fmt(f)
+    }
+}
@@ -0,0 +1,261 @@
+use core::{
+    fmt,
+    hint::{assert_unchecked, unreachable_unchecked},
+    ptr::{self, NonNull},
+};
+
+use crate::{layout::HasLayout, strict};
+
+use super::{Tag, TagPtr};
+
+/// A tagged pointer that may be null, while still carrying a tag.
+///
+/// This is a single word, like `Option<TagPtr<T>>`, except that the null state
+/// keeps its tag instead of collapsing into a tagless `None`.
+#[repr(transparent)]
+pub struct NullableTagPtr<T> {
+    raw: *mut T,
+}
+
+impl<T> NullableTagPtr<T> {
+    /// Create a null tagged pointer.
+    #[inline]
+    #[must_use]
+    pub const fn none(tag: Tag<T>) -> NullableTagPtr<T> {
+        NullableTagPtr {
+            raw: strict::without_provenance_mut(tag.get()),
+        }
+    }
+
+    /// Create a non-null tagged pointer.
+    #[inline]
+    #[must_use]
+    pub const fn some(ptr: TagPtr<T>) -> NullableTagPtr<T> {
+        NullableTagPtr {
+            raw: ptr.raw.as_ptr(),
+        }
+    }
+
+    /// Try to create a tagged pointer to a `T`.
+    ///
+    /// Returns `None` if `ptr` is not aligned for `T`.
+    #[inline]
+    #[must_use]
+    pub fn try_new(ptr: *mut T, tag: Tag<T>) -> Option<NullableTagPtr<T>> {
+        if ptr.is_aligned() {
+            // Since `ptr` is aligned, the tag will never conflict with the address.
+            let raw = strict::map_addr(ptr, |addr| addr | tag.get());
+
+            Some(NullableTagPtr { raw })
+        } else {
+            None
+        }
+    }
+
+    /// Create a tagged pointer to a `T` without safety checks.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub unsafe fn new_unchecked(ptr: *mut T, tag: Tag<T>) -> NullableTagPtr<T> {
+        match NullableTagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None if cfg!(debug_assertions) => panic!("`ptr` is not aligned"),
+            // SAFETY: The caller ensures that `ptr` is aligned properly.
+            None => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Create a tagged pointer to a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ptr` is not properly aligned for `T`.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn new(ptr: *mut T, tag: Tag<T>) -> NullableTagPtr<T> {
+        match NullableTagPtr::<T>::try_new(ptr, tag) {
+            Some(ptr) => ptr,
+            None => panic!("`ptr` is not aligned"),
+        }
+    }
+
+    /// Returns whether the pointer is null, ignoring the tag.
+    #[inline]
+    #[must_use]
+    pub fn is_none(self) -> bool {
+        strict::addr(self.raw) & T::PTR_MASK == 0
+    }
+
+    /// Returns whether the pointer is non-null, ignoring the tag.
+    #[inline]
+    #[must_use]
+    pub fn is_some(self) -> bool {
+        !self.is_none()
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(self) -> Tag<T> {
+        let tag = strict::addr(self.raw) & T::TAG_MASK;
+
+        Tag::new(tag).unwrap()
+    }
+
+    /// Get the actual pointer, which may be null.
+    #[inline]
+    #[must_use]
+    pub fn ptr(self) -> *mut T {
+        let ptr = strict::map_addr(self.raw, |addr| addr & T::PTR_MASK);
+
+        // SAFETY: Creating a tagged pointer requires that the pointer without the tag
+        //         is properly aligned for `T`.
+        unsafe { assert_unchecked(ptr.is_aligned()) };
+
+        ptr
+    }
+
+    /// Get the actual pointer, or `None` if it is null.
+    #[inline]
+    #[must_use]
+    pub fn as_ptr(self) -> Option<NonNull<T>> {
+        NonNull::new(self.ptr())
+    }
+
+    /// Get the tagged pointer, or `None` if it is null.
+    #[inline]
+    #[must_use]
+    pub fn as_tag_ptr(self) -> Option<TagPtr<T>> {
+        if self.is_some() {
+            // SAFETY: The address is non-null and aligned, so the tagged address is non-null
+            //         and a valid `TagPtr<T>`.
+            Some(unsafe { TagPtr::from_raw_unchecked(self.raw) })
+        } else {
+            None
+        }
+    }
+
+    /// Create a new tagged pointer with the same address as `self`.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: Tag<T>) -> NullableTagPtr<T> {
+        // SAFETY: We already know that `ptr` is valid, as it must be in order to construct
+        //         a tagged pointer.
+        unsafe { NullableTagPtr::new_unchecked(self.ptr(), tag) }
+    }
+
+    /// Create a new tagged pointer with the same tag as `self`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - The resulting pointer has the same provenance as `ptr`, not `self`.
+    #[inline]
+    #[must_use]
+    pub unsafe fn with_ptr(self, ptr: *mut T) -> NullableTagPtr<T> {
+        debug_assert!(ptr.is_aligned(), "`ptr` is not aligned");
+
+        // SAFETY: The caller ensures that `ptr` is valid.
+        unsafe { NullableTagPtr::new_unchecked(ptr, self.tag()) }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: Tag<T>) {
+        *self = self.with_tag(tag);
+    }
+
+    /// Update the pointer.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `ptr` is properly aligned for `T`.
+    /// - `self` will have the same provenance as `ptr` after this call.
+    #[inline]
+    pub unsafe fn set_ptr(&mut self, ptr: *mut T) {
+        *self = self.with_ptr(ptr);
+    }
+}
+
+impl<T> From<TagPtr<T>> for NullableTagPtr<T> {
+    #[inline]
+    fn from(ptr: TagPtr<T>) -> Self {
+        NullableTagPtr::some(ptr)
+    }
+}
+
+impl<T> From<Option<TagPtr<T>>> for NullableTagPtr<T> {
+    #[inline]
+    fn from(ptr: Option<TagPtr<T>>) -> Self {
+        match ptr {
+            Some(ptr) => NullableTagPtr::some(ptr),
+            None => NullableTagPtr::none(Tag::MIN),
+        }
+    }
+}
+
+impl<T> Default for NullableTagPtr<T> {
+    #[inline]
+    fn default() -> Self {
+        NullableTagPtr::none(Tag::MIN)
+    }
+}
+
+impl<T> fmt::Debug for NullableTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NullableTagPtr")
+            .field("ptr", &self.ptr())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+impl<T> fmt::Pointer for NullableTagPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ptr().fmt(f)
+    }
+}
+
+impl<T> Clone for NullableTagPtr<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NullableTagPtr<T> {}
+
+impl<T> PartialEq for NullableTagPtr<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.raw, other.raw)
+    }
+}
+
+impl<T> Eq for NullableTagPtr<T> {}
+
+impl<T> PartialOrd for NullableTagPtr<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for NullableTagPtr<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> core::hash::Hash for NullableTagPtr<T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
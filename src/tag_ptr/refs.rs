@@ -0,0 +1,237 @@
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use super::{Tag, TagPtr};
+
+/// A shared reference to a `T` with a tag stored in its alignment bits.
+///
+/// This is a single word, and unlike a [`TagPtr`] it is safe to dereference.
+#[repr(transparent)]
+pub struct TagRef<'a, T> {
+    ptr: TagPtr<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+// SAFETY: `TagRef<'a, T>` behaves like `&'a T`.
+unsafe impl<T: Sync> Send for TagRef<'_, T> {}
+// SAFETY: `TagRef<'a, T>` behaves like `&'a T`.
+unsafe impl<T: Sync> Sync for TagRef<'_, T> {}
+
+impl<'a, T> TagRef<'a, T> {
+    /// Create a tagged reference.
+    #[inline]
+    #[must_use]
+    pub const fn new(r: &'a T, tag: Tag<T>) -> TagRef<'a, T> {
+        TagRef {
+            ptr: TagPtr::from_ref(r, tag),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the reference, with the original lifetime.
+    #[inline]
+    #[must_use]
+    pub fn get(self) -> &'a T {
+        // SAFETY: The pointer came from a `&'a T`.
+        unsafe { self.ptr.ptr().as_ref() }
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(self) -> Tag<T> {
+        self.ptr.tag()
+    }
+
+    /// Get the underlying tagged pointer.
+    #[inline]
+    #[must_use]
+    pub const fn as_tag_ptr(self) -> TagPtr<T> {
+        self.ptr
+    }
+
+    /// Create a new tagged reference to the same value.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: Tag<T>) -> TagRef<'a, T> {
+        TagRef {
+            ptr: self.ptr.with_tag(tag),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: Tag<T>) {
+        self.ptr.set_tag(tag);
+    }
+
+    /// Reborrow with a shorter lifetime.
+    #[inline]
+    #[must_use]
+    pub fn reborrow(&self) -> TagRef<'_, T> {
+        *self
+    }
+}
+
+impl<T> Deref for TagRef<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T> AsRef<T> for TagRef<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T> Clone for TagRef<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TagRef<'_, T> {}
+
+impl<'a, T> From<TagMut<'a, T>> for TagRef<'a, T> {
+    #[inline]
+    fn from(r: TagMut<'a, T>) -> Self {
+        TagRef {
+            ptr: r.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TagRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TagRef")
+            .field("value", self.get())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+/// A mutable reference to a `T` with a tag stored in its alignment bits.
+///
+/// This is a single word, and unlike a [`TagPtr`] it is safe to dereference.
+#[repr(transparent)]
+pub struct TagMut<'a, T> {
+    ptr: TagPtr<T>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+// SAFETY: `TagMut<'a, T>` behaves like `&'a mut T`.
+unsafe impl<T: Send> Send for TagMut<'_, T> {}
+// SAFETY: `TagMut<'a, T>` behaves like `&'a mut T`.
+unsafe impl<T: Sync> Sync for TagMut<'_, T> {}
+
+impl<'a, T> TagMut<'a, T> {
+    /// Create a tagged mutable reference.
+    #[inline]
+    #[must_use]
+    pub const fn new(r: &'a mut T, tag: Tag<T>) -> TagMut<'a, T> {
+        TagMut {
+            ptr: TagPtr::from_mut(r, tag),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Convert into the mutable reference with the original lifetime.
+    #[inline]
+    #[must_use]
+    pub fn into_mut(self) -> &'a mut T {
+        // SAFETY: The pointer came from a `&'a mut T`, and we are consumed.
+        unsafe { self.ptr.ptr().as_mut() }
+    }
+
+    /// Get the tag.
+    #[inline]
+    #[must_use]
+    pub fn tag(&self) -> Tag<T> {
+        self.ptr.tag()
+    }
+
+    /// Convert into the underlying tagged pointer.
+    #[inline]
+    #[must_use]
+    pub const fn into_tag_ptr(self) -> TagPtr<T> {
+        self.ptr
+    }
+
+    /// Create a new tagged reference to the same value.
+    #[inline]
+    #[must_use]
+    pub fn with_tag(self, tag: Tag<T>) -> TagMut<'a, T> {
+        TagMut {
+            ptr: self.ptr.with_tag(tag),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Update the tag.
+    #[inline]
+    pub fn set_tag(&mut self, tag: Tag<T>) {
+        self.ptr.set_tag(tag);
+    }
+
+    /// Reborrow with a shorter lifetime.
+    #[inline]
+    #[must_use]
+    pub fn reborrow(&mut self) -> TagMut<'_, T> {
+        TagMut {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for TagMut<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: The pointer came from a `&'a mut T`, and we are borrowed immutably.
+        unsafe { self.ptr.ptr().as_ref() }
+    }
+}
+
+impl<T> DerefMut for TagMut<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The pointer came from a `&'a mut T`, and we are borrowed mutably.
+        unsafe { self.ptr.ptr().as_mut() }
+    }
+}
+
+impl<T> AsRef<T> for TagMut<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for TagMut<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TagMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TagMut")
+            .field("value", &**self)
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
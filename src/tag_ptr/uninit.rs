@@ -0,0 +1,76 @@
+use core::{mem::MaybeUninit, ptr::NonNull};
+
+use super::{Tag, TagPtr};
+
+impl<T> TagPtr<MaybeUninit<T>> {
+    /// The tag bit that marks the pointee as initialized.
+    ///
+    /// This is conventionally the lowest alignment bit, the rest are free to use. Using
+    /// it fails compilation if `T` has no alignment bits.
+    pub const INIT: Tag<MaybeUninit<T>> = match Tag::bit(0) {
+        Some(tag) => tag,
+        None => panic!("tracking initialization requires at least one alignment bit"),
+    };
+
+    /// Returns whether the pointee is marked as initialized.
+    #[inline]
+    #[must_use]
+    pub fn is_init(self) -> bool {
+        (self.tag() & Self::INIT) == Self::INIT
+    }
+
+    /// Mark the pointee as initialized.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the pointee is initialized.
+    #[inline]
+    pub unsafe fn mark_init(&mut self) {
+        self.update_tag(|tag| tag | Self::INIT);
+    }
+
+    /// Mark the pointee as uninitialized.
+    ///
+    /// This does not drop the pointee.
+    #[inline]
+    pub fn mark_uninit(&mut self) {
+        self.update_tag(|tag| tag & !Self::INIT);
+    }
+
+    /// Initialize the pointee with `value` and mark it as initialized.
+    ///
+    /// This does not drop any value that was previously initialized.
+    ///
+    /// # Safety
+    ///
+    /// - The untagged pointer must be valid for writes.
+    #[inline]
+    pub unsafe fn write(&mut self, value: T) {
+        // SAFETY: The caller ensures that the pointer is valid for writes.
+        unsafe { self.ptr().write(MaybeUninit::new(value)) };
+
+        // SAFETY: We just initialized the pointee.
+        unsafe { self.mark_init() };
+    }
+
+    /// Get a pointer to the initialized `T`, or `None` if the pointee is not marked
+    /// as initialized.
+    #[inline]
+    #[must_use]
+    pub fn init_ptr(self) -> Option<NonNull<T>> {
+        if self.is_init() {
+            Some(self.assume_init_ptr())
+        } else {
+            None
+        }
+    }
+
+    /// Get a pointer to the `T`, whether or not it is marked as initialized.
+    ///
+    /// Reading through the result is only valid if the pointee is initialized.
+    #[inline]
+    #[must_use]
+    pub fn assume_init_ptr(self) -> NonNull<T> {
+        self.ptr().cast()
+    }
+}
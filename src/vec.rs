@@ -0,0 +1,2643 @@
+//! A reference counted, copy-on-write vector.
+
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
+use core::{
+    alloc::Layout,
+    any::TypeId,
+    borrow::Borrow,
+    cell::Cell,
+    cmp, fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    ptr::{self, NonNull},
+    slice::{self, SliceIndex},
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    layout::HasLayout,
+    raw::{handle_reserve_error, AtomicData, Data, Header, Kind},
+    slice::SharedSlice,
+    strict,
+    sync::AtomicUsize,
+    tag_ptr::Tag,
+};
+
+mod chunks;
+mod cow_guard;
+mod drain;
+mod extract_if;
+mod into_iter;
+mod ref_count;
+mod splice;
+mod unique;
+
+pub use chunks::{ChunksShared, SplitShared};
+pub use cow_guard::CowGuard;
+pub use drain::Drain;
+pub use extract_if::ExtractIf;
+pub use into_iter::{IntoChunksExact, IntoIter};
+pub use ref_count::RefCount;
+pub use splice::Splice;
+pub use unique::UniqueVec;
+
+/// The amount of words in a handle that are used to store elements inline.
+const INLINE_WORDS: usize = 2;
+
+/// A reference counted vector, where cloning only bumps the reference count.
+///
+/// The handle is a pointer to the data, with the capacity, length and reference count
+/// stored in a header right before it, along with the range of elements the handle
+/// sees, so that slices can share one allocation.
+///
+/// Vectors that are small enough are stored inline in the handle instead, without
+/// allocating, and vectors created from static data borrow it without a header at all.
+///
+/// A handle remembers when it is the only one to its allocation, so mutating a vector
+/// that was never cloned doesn't touch the reference count at all.
+///
+/// The reference count is an [`AtomicUsize`] by default, see [`LocalSharedVec`] for a
+/// vector that only lives on one thread.
+///
+/// Allocations come from the [`Global`] allocator by default. Every handle keeps its own
+/// clone of the allocator, and whichever handle is dropped last frees the allocation
+/// with it.
+///
+/// How the elements are stored is kept in the two low bits of the pointer to the
+/// header, so the header has to be aligned to at least 4 bytes. It is as aligned as a
+/// `usize` and the elements, which means that on 16-bit targets, such as AVR and MSP430,
+/// only vectors of elements aligned to at least 4 bytes compile.
+pub struct SharedVec<T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    data: AtomicData<T, R>,
+    storage: Storage<T>,
+    alloc: A,
+    _marker: PhantomData<T>,
+}
+
+/// A [`SharedVec`] with a non-atomic reference count, which can't be sent to or shared
+/// with other threads.
+pub type LocalSharedVec<T, A = Global> = SharedVec<T, Cell<usize>, A>;
+
+/// The words of a handle besides [`Data`], whose contents depend on [`Kind`].
+union Storage<T> {
+    /// The elements this handle sees, for [`Kind::Heap`] and [`Kind::Static`].
+    view: View<T>,
+    /// The elements themselves, for [`Kind::Inline`].
+    inline: MaybeUninit<[usize; INLINE_WORDS]>,
+}
+
+/// A pointer to some elements and their length.
+struct View<T> {
+    ptr: NonNull<T>,
+    len: usize,
+}
+
+impl<T> Clone for View<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for View<T> {}
+
+// SAFETY: Like `Arc<[T]>`, elements can be shared and dropped from any thread, as long
+//         as the reference count can be updated from any thread.
+unsafe impl<T: Send + Sync, R: RefCount + Send + Sync, A: Allocator + Send + Sync> Send
+    for SharedVec<T, R, A>
+{
+}
+
+// SAFETY: Like `Arc<[T]>`, elements can be shared and dropped from any thread, as long
+//         as the reference count can be updated from any thread.
+unsafe impl<T: Send + Sync, R: RefCount + Send + Sync, A: Allocator + Send + Sync> Sync
+    for SharedVec<T, R, A>
+{
+}
+
+impl<T, R: RefCount> SharedVec<T, R> {
+    /// Create an empty vector, without allocating.
+    #[inline]
+    #[must_use]
+    pub fn new() -> SharedVec<T, R> {
+        SharedVec::new_in(Global)
+    }
+
+    /// Create a vector that borrows static data, without allocating.
+    ///
+    /// Cloning and dropping such a vector does nothing, and mutating it first clones the
+    /// elements into a new allocation.
+    #[inline]
+    #[must_use]
+    pub const fn from_static(elems: &'static [T]) -> SharedVec<T, R> {
+        SharedVec::from_static_in(elems, Global)
+    }
+
+    /// Create an empty vector with room for at least `cap` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn with_capacity(cap: usize) -> SharedVec<T, R> {
+        SharedVec::with_capacity_in(cap, Global)
+    }
+
+    /// Try to create an empty vector with room for at least `cap` elements.
+    #[inline]
+    pub fn try_with_capacity(cap: usize) -> Result<SharedVec<T, R>, TryReserveError> {
+        SharedVec::try_with_capacity_in(cap, Global)
+    }
+
+    /// Create a vector of `len` elements, where each element is returned by calling `f`
+    /// with its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn from_fn(len: usize, mut f: impl FnMut(usize) -> T) -> SharedVec<T, R> {
+        let mut vec = SharedVec::with_capacity(len);
+
+        // SAFETY: The vector was just created.
+        let mut guard = unsafe { SetLenOnDrop::new(&mut vec) };
+
+        for index in 0..len {
+            // SAFETY: The vector has room for `len` elements.
+            unsafe { guard.push(f(index)) };
+        }
+
+        drop(guard);
+
+        vec
+    }
+
+    /// Try to turn the vector into a `Vec<T>`, if this is the only handle to the elements.
+    ///
+    /// The allocation is reused when its layout allows it, the same way as converting
+    /// from a `Vec<T>` does, otherwise the elements are moved into a new allocation.
+    /// Elements of a heap vector that are outside of this handle's view are dropped.
+    ///
+    /// An empty vector of static data, such as one created with [`SharedVec::new`],
+    /// always turns into an empty `Vec<T>`.
+    pub fn try_unwrap(self) -> Result<Vec<T>, SharedVec<T, R>> {
+        let empty_static = self.data().kind() == Kind::Static && self.is_empty();
+
+        if !self.is_unique() && !empty_static {
+            return Err(self);
+        }
+
+        let mut this = ManuallyDrop::new(self);
+
+        if matches!(this.data().kind(), Kind::Heap | Kind::Unique) {
+            // SAFETY: This is the only handle to the heap allocation.
+            unsafe { this.normalize() };
+
+            let data = this.data();
+            let len = this.len();
+
+            if let Some(cap) = Header::<T, R>::vec_capacity(this.header().cap) {
+                // SAFETY: `vec_capacity` ensures that the allocation has the same layout
+                //         as a `Vec<T>` with capacity `cap`, and nothing else can access
+                //         the header, so the elements can be moved over it.
+                unsafe {
+                    let ptr = data.header().cast::<T>().as_ptr();
+
+                    ptr::copy(data.data().as_ptr(), ptr, len);
+
+                    return Ok(Vec::from_raw_parts(ptr, len, cap));
+                }
+            }
+        }
+
+        let len = this.len();
+        let mut vec = Vec::with_capacity(len);
+
+        // SAFETY: The elements are moved out of the handle before its storage is freed,
+        //         and nothing else can access them.
+        unsafe {
+            ptr::copy_nonoverlapping(this.as_ptr(), vec.as_mut_ptr(), len);
+            vec.set_len(len);
+
+            if matches!(this.data().kind(), Kind::Heap | Kind::Unique) {
+                this.data().deallocate(&this.alloc);
+            }
+        }
+
+        Ok(vec)
+    }
+
+    /// Decompose the vector into its raw parts, without dropping it.
+    ///
+    /// The parts are an opaque pointer to the allocation, which also encodes how the
+    /// elements are stored, and a pointer to the elements this handle sees along with
+    /// their length. They can be stored anywhere, including across FFI boundaries, and
+    /// must eventually be turned back into a vector with [`SharedVec::from_raw_parts`],
+    /// or the elements are leaked.
+    ///
+    /// Elements that are stored inline are moved to the heap first, since they live in
+    /// the handle itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are stored inline, and moving them to the heap fails.
+    #[must_use]
+    #[track_caller]
+    pub fn into_raw_parts(self) -> (*const (), *const T, usize) {
+        let (data, ptr, len, Global) = self.into_raw_parts_with_alloc();
+
+        (data, ptr, len)
+    }
+
+    /// Reassemble a vector from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `data` was returned by [`SharedVec::into_raw_parts`]
+    ///   for a vector with the same `T` and `R`, and that each set of parts is only
+    ///   reassembled once.
+    /// - `ptr` and `len` must describe the elements returned along with `data`, or a
+    ///   contiguous part of them.
+    #[inline]
+    #[must_use]
+    pub unsafe fn from_raw_parts(data: *const (), ptr: *const T, len: usize) -> SharedVec<T, R> {
+        // SAFETY: The caller ensures that this is valid.
+        unsafe { SharedVec::from_raw_parts_in(data, ptr, len, Global) }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> SharedVec<T, R, A> {
+    /// The amount of elements that can be stored inline.
+    ///
+    /// Zero-sized elements take no room, so only their length has to fit in the handle.
+    const INLINE_CAP: usize = if T::IS_ZST {
+        (usize::MAX >> Header::<T, R>::ALIGN_BITS) - 1
+    } else if T::ALIGN.get() > mem::align_of::<usize>() {
+        0
+    } else {
+        mem::size_of::<[usize; INLINE_WORDS]>() / T::SIZE
+    };
+
+    /// Create an empty vector that allocates from `alloc` once it is mutated.
+    ///
+    /// This doesn't allocate. If the elements can't be stored inline, the vector acts
+    /// like it borrows an empty static slice, see [`SharedVec::from_static_in`].
+    #[inline]
+    #[must_use]
+    pub fn new_in(alloc: A) -> SharedVec<T, R, A> {
+        if Self::INLINE_CAP > 0 {
+            return SharedVec::with_capacity_in(0, alloc);
+        }
+
+        SharedVec {
+            data: AtomicData::new(Data::dangling(Tag::new_const::<{ Kind::Static as usize }>())),
+            storage: Storage {
+                view: View {
+                    ptr: NonNull::dangling(),
+                    len: 0,
+                },
+            },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a vector that borrows static data, and allocates from `alloc` once it is
+    /// mutated.
+    #[inline]
+    #[must_use]
+    pub const fn from_static_in(elems: &'static [T], alloc: A) -> SharedVec<T, R, A> {
+        SharedVec {
+            data: AtomicData::new(Data::dangling(Tag::new_const::<{ Kind::Static as usize }>())),
+            storage: Storage {
+                view: View {
+                    // SAFETY: References are never null.
+                    ptr: unsafe { NonNull::new_unchecked(elems.as_ptr().cast_mut()) },
+                    len: elems.len(),
+                },
+            },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the allocator the vector allocates from.
+    #[inline]
+    #[must_use]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Get the pointer to the data, and how the elements are stored.
+    #[inline]
+    fn data(&self) -> Data<T, R> {
+        self.data.get()
+    }
+
+    /// Get the view of the elements.
+    #[inline]
+    fn view(&self) -> View<T> {
+        debug_assert_ne!(self.data().kind(), Kind::Inline);
+
+        // SAFETY: Heap and static vectors always store a view.
+        unsafe { self.storage.view }
+    }
+
+    /// Replace the header of a heap vector, viewing every element in it.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `data` is a valid header, and that the old header
+    ///   is no longer needed.
+    #[inline]
+    unsafe fn set_data(&mut self, data: Data<T, R>) {
+        // SAFETY: The caller ensures that the header is valid.
+        let len = unsafe { data.header().as_ref().len };
+
+        self.data.set(data);
+        self.storage.view = View {
+            ptr: data.data(),
+            len,
+        };
+    }
+
+    /// Get the offset of the view of a heap vector from the start of the allocation.
+    ///
+    /// Zero-sized elements all have the same address, so views of them always start at
+    /// the beginning of the allocation and only differ in length.
+    #[inline]
+    fn view_offset(&self) -> usize {
+        if T::IS_ZST {
+            return 0;
+        }
+
+        // SAFETY: The view is always within the allocation.
+        unsafe { self.view().ptr.offset_from(self.data().data()) as usize }
+    }
+
+    /// Make the view of a unique heap vector start at the beginning of the allocation,
+    /// and cover every element in it.
+    ///
+    /// Elements outside of the view are dropped. If dropping one of them panics, the
+    /// rest of the elements are leaked.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that this is a heap vector, and the only handle to
+    ///   the allocation.
+    unsafe fn normalize(&mut self) {
+        let view = self.view();
+        let data = self.data().data();
+        let header = self.data().header().as_ptr();
+
+        // SAFETY: The view is always within the initialized elements of the allocation,
+        //         and the caller ensures that nothing else can access them.
+        unsafe {
+            let total = (*header).len;
+            let offset = self.view_offset();
+
+            if offset == 0 && view.len == total {
+                return;
+            }
+
+            (*header).len = 0;
+            self.storage.view.len = 0;
+
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(data.as_ptr(), offset));
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                view.ptr.as_ptr().add(view.len),
+                total - offset - view.len,
+            ));
+
+            ptr::copy(view.ptr.as_ptr(), data.as_ptr(), view.len);
+
+            (*header).len = view.len;
+            self.storage.view = View {
+                ptr: data,
+                len: view.len,
+            };
+        }
+    }
+
+    /// Create an empty vector with room for at least `cap` elements, that allocates
+    /// from `alloc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> SharedVec<T, R, A> {
+        match SharedVec::try_with_capacity_in(cap, alloc) {
+            Ok(vec) => vec,
+            Err(error) => handle_reserve_error(error),
+        }
+    }
+
+    /// Try to create an empty vector with room for at least `cap` elements, that
+    /// allocates from `alloc`.
+    #[inline]
+    pub fn try_with_capacity_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<SharedVec<T, R, A>, TryReserveError> {
+        if Self::INLINE_CAP > 0 && cap <= Self::INLINE_CAP {
+            return Ok(SharedVec {
+                data: AtomicData::new(Data::inline(0)),
+                storage: Storage {
+                    inline: MaybeUninit::uninit(),
+                },
+                alloc,
+                _marker: PhantomData,
+            });
+        }
+
+        let data = Data::try_allocate(cap, &alloc)?;
+
+        Ok(SharedVec {
+            data: AtomicData::new(data),
+            storage: Storage {
+                view: View {
+                    ptr: data.data(),
+                    len: 0,
+                },
+            },
+            alloc,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Get the header.
+    #[inline]
+    fn header(&self) -> &Header<T, R> {
+        // SAFETY: The header is valid for as long as there is a handle to it.
+        unsafe { self.data().header().as_ref() }
+    }
+
+    /// Get the amount of elements in the vector.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self.data().kind() {
+            Kind::Heap | Kind::Unique | Kind::Static => self.view().len,
+            Kind::Inline => self.data().inline_len(),
+        }
+    }
+
+    /// Get the amount of elements the vector has room for, counting from the start
+    /// of its view into the allocation.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        match self.data().kind() {
+            Kind::Heap | Kind::Unique => self.header().cap - self.view_offset(),
+            Kind::Inline => Self::INLINE_CAP,
+            Kind::Static => self.view().len,
+        }
+    }
+
+    /// Get the size of the allocation the elements are stored in, in bytes, including
+    /// the header.
+    ///
+    /// This is zero if the elements are stored inline or borrowed from static data. The
+    /// allocation is shared by every handle to it, so summing this over handles counts
+    /// shared allocations more than once.
+    #[inline]
+    #[must_use]
+    pub fn allocated_bytes(&self) -> usize {
+        match self.data().kind() {
+            Kind::Heap | Kind::Unique => {
+                // SAFETY: The layout was valid when the header was allocated.
+                let (layout, _) = unsafe { Header::<T, R>::layout_unchecked(self.header().cap) };
+
+                layout.size()
+            }
+            Kind::Inline | Kind::Static => 0,
+        }
+    }
+
+    /// Get the amount of bytes each allocation uses before the elements, for the
+    /// capacity, length and reference count.
+    #[inline]
+    #[must_use]
+    pub const fn header_overhead() -> usize {
+        Header::<T, R>::DATA_OFFSET
+    }
+
+    /// Returns whether the vector has no elements.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether this is the only handle to the elements, so that mutating them
+    /// doesn't have to clone them first.
+    ///
+    /// Vectors of static data are never unique.
+    #[inline]
+    #[must_use]
+    pub fn is_unique(&self) -> bool {
+        match self.data().kind() {
+            Kind::Heap => self.header().ref_count.is_one(),
+            Kind::Unique | Kind::Inline => true,
+            Kind::Static => false,
+        }
+    }
+
+    /// Get the amount of handles to the elements.
+    ///
+    /// Vectors that are stored inline always have a count of one, and vectors of static
+    /// data aren't counted at all, so they have a count of zero. Other threads may clone
+    /// or drop handles at any time, so use [`SharedVec::is_unique`] to decide whether the
+    /// elements can be mutated.
+    #[inline]
+    #[must_use]
+    pub fn strong_count(&self) -> usize {
+        match self.data().kind() {
+            Kind::Heap => self.header().ref_count.count(),
+            Kind::Unique | Kind::Inline => 1,
+            Kind::Static => 0,
+        }
+    }
+
+    /// Get the elements mutably if this is the only handle to them, without cloning.
+    ///
+    /// Like `Arc::get_mut`, this is an associated function, and is called as
+    /// `SharedVec::get_mut(&mut vec)`.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(this: &mut Self) -> Option<&mut [T]> {
+        if !this.is_unique() {
+            return None;
+        }
+
+        if this.data().kind() == Kind::Heap {
+            this.data.set(this.data().with_kind(Kind::Unique));
+        }
+
+        // SAFETY: This is the only handle to the elements, and it is borrowed mutably.
+        Some(unsafe { slice::from_raw_parts_mut(this.as_mut_ptr(), this.len()) })
+    }
+
+    /// Get a raw pointer to the elements.
+    #[inline]
+    fn as_ptr(&self) -> *const T {
+        match self.data().kind() {
+            Kind::Heap | Kind::Unique | Kind::Static => self.view().ptr.as_ptr(),
+            // The inline storage may not be aligned for zero-sized elements, which don't
+            // need any storage anyway.
+            Kind::Inline if T::IS_ZST => NonNull::dangling().as_ptr(),
+            // SAFETY: Taking a pointer to the inline storage doesn't read it.
+            Kind::Inline => unsafe { self.storage.inline.as_ptr().cast() },
+        }
+    }
+
+    /// Get a raw mutable pointer to the elements.
+    #[inline]
+    fn as_mut_ptr(&mut self) -> *mut T {
+        match self.data().kind() {
+            Kind::Heap | Kind::Unique | Kind::Static => self.view().ptr.as_ptr(),
+            Kind::Inline if T::IS_ZST => NonNull::dangling().as_ptr(),
+            // SAFETY: Taking a pointer to the inline storage doesn't read it.
+            Kind::Inline => unsafe { self.storage.inline.as_mut_ptr().cast() },
+        }
+    }
+
+    /// Set the length of the vector, without dropping or initializing any elements.
+    ///
+    /// This is meant for initializing elements through
+    /// [`SharedVec::spare_capacity_mut`], like [`Vec::set_len`].
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that this is the only handle to the elements, and that
+    ///   its view covers the whole allocation. This is the case right after a method
+    ///   that mutates the vector, such as [`SharedVec::reserve`] or
+    ///   [`SharedVec::spare_capacity_mut`], as long as it hasn't been cloned or sliced
+    ///   since.
+    /// - The caller must ensure that `len` is at most [`SharedVec::capacity`], and that
+    ///   the first `len` elements are initialized.
+    #[inline]
+    pub unsafe fn set_len(&mut self, len: usize) {
+        match self.data().kind() {
+            Kind::Heap | Kind::Unique => {
+                // SAFETY: The caller ensures that this is the only handle to the header.
+                unsafe { (*self.data().header().as_ptr()).len = len };
+                self.storage.view.len = len;
+            }
+            Kind::Inline => self.data.set(Data::inline(len)),
+            Kind::Static => unreachable!("static vectors are never unique"),
+        }
+    }
+
+    /// Compute the capacity needed for `additional` more elements than the length.
+    ///
+    /// If the elements don't fit and `exact` is false, the capacity is at least doubled.
+    fn grown_capacity(&self, additional: usize, exact: bool) -> Result<usize, TryReserveError> {
+        let len = self.len();
+        let cap = self.capacity();
+
+        let Some(required) = len.checked_add(additional) else {
+            return Err(TryReserveError::CapacityOverflow);
+        };
+
+        if cap - len >= additional || exact {
+            Ok(required)
+        } else {
+            Ok(cmp::max(cmp::min(cap.saturating_mul(2), T::MAX_SLICE_LEN), required).max(4))
+        }
+    }
+
+    /// Try to ensure that there is room for at least `additional` more elements.
+    ///
+    /// If the vector has to grow and `exact` is false, the capacity is at least doubled.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that this is the only handle to the elements, and that
+    ///   the view of a heap vector is normalized, see [`SharedVec::normalize`]. Vectors of
+    ///   static data must be empty.
+    unsafe fn try_grow(&mut self, additional: usize, exact: bool) -> Result<(), TryReserveError> {
+        let len = self.len();
+
+        if self.capacity() - len >= additional {
+            return Ok(());
+        }
+
+        let new_cap = self.grown_capacity(additional, exact)?;
+
+        match self.data().kind() {
+            // SAFETY: The caller ensures that this is the only handle to the header, and
+            //         the new capacity is at least the length.
+            Kind::Heap | Kind::Unique => unsafe {
+                self.set_data(self.data().try_reallocate(new_cap, &self.alloc)?)
+            },
+            Kind::Inline | Kind::Static => {
+                debug_assert!(self.data().kind() == Kind::Inline || len == 0);
+
+                let data = Data::try_allocate(new_cap, &self.alloc)?;
+
+                // SAFETY: The new allocation has room for the elements, and they are no
+                //         longer considered part of the inline storage once they are moved.
+                unsafe {
+                    ptr::copy_nonoverlapping(self.as_ptr(), data.data().as_ptr(), len);
+                    (*data.header().as_ptr()).len = len;
+
+                    self.set_data(data);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shorten the vector to `len` elements, dropping the rest.
+    ///
+    /// If the elements are shared, the view of this handle is shrunk instead, and the
+    /// elements are dropped along with the last handle to them. This does nothing if
+    /// `len` is at least the current length.
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.len();
+
+        if len >= old_len {
+            return;
+        }
+
+        if !self.is_unique() {
+            // Shrinking the view only hides elements from this handle.
+            self.storage.view.len = len;
+            return;
+        }
+
+        if matches!(self.data().kind(), Kind::Heap | Kind::Unique) {
+            self.data.set(self.data().with_kind(Kind::Unique));
+
+            // SAFETY: This is the only handle to the heap allocation.
+            unsafe { self.normalize() };
+        }
+
+        // SAFETY: The vector is unique and normalized, and the tail is no longer
+        //         considered part of it before it is dropped, so if dropping an element
+        //         panics the rest are leaked rather than dropped twice.
+        unsafe {
+            self.set_len(len);
+
+            let tail = ptr::slice_from_raw_parts_mut(self.as_mut_ptr().add(len), old_len - len);
+
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    /// Shrink the capacity of the vector to its length.
+    ///
+    /// This only reallocates if this is the only handle to the elements, since other
+    /// handles still point to the current allocation. If the elements fit inline, the
+    /// allocation is freed entirely.
+    pub fn shrink_to_fit(&mut self) {
+        if !matches!(self.data().kind(), Kind::Heap | Kind::Unique) || !self.is_unique() {
+            return;
+        }
+
+        // SAFETY: This is the only handle to the heap allocation.
+        unsafe { self.normalize() };
+
+        let len = self.len();
+
+        if self.capacity() == len {
+            return;
+        }
+
+        if Self::INLINE_CAP > 0 && len <= Self::INLINE_CAP {
+            let heap = self.data();
+
+            // SAFETY: This is the only handle to the header, and the elements fit inline.
+            //         They are moved before the header is deallocated.
+            unsafe {
+                self.data.set(Data::inline(len));
+
+                ptr::copy_nonoverlapping(heap.data().as_ptr(), self.as_mut_ptr(), len);
+                heap.deallocate(&self.alloc);
+            }
+        } else {
+            // SAFETY: This is the only handle to the header, and the new capacity is
+            //         the length.
+            unsafe { self.set_data(self.data().reallocate(len, &self.alloc)) };
+        }
+    }
+
+    /// Decompose the vector into its raw parts, along with its allocator.
+    ///
+    /// See [`SharedVec::into_raw_parts`] for what the parts are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are stored inline, and moving them to the heap fails.
+    #[must_use]
+    #[track_caller]
+    pub fn into_raw_parts_with_alloc(mut self) -> (*const (), *const T, usize, A) {
+        self.move_inline_to_heap();
+
+        let this = ManuallyDrop::new(self);
+        let view = this.view();
+
+        // SAFETY: The allocator is moved out of a handle that is never dropped.
+        let alloc = unsafe { ptr::read(&this.alloc) };
+
+        (
+            this.data().into_raw().cast_const().cast(),
+            view.ptr.as_ptr().cast_const(),
+            view.len,
+            alloc,
+        )
+    }
+
+    /// Move the elements to a new heap allocation if they are stored inline, so that
+    /// they can outlive the handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if allocating fails.
+    #[track_caller]
+    fn move_inline_to_heap(&mut self) {
+        if self.data().kind() != Kind::Inline {
+            return;
+        }
+
+        let len = self.len();
+        let data = Data::allocate(len, &self.alloc);
+
+        // SAFETY: The new allocation has room for the elements, and they are no longer
+        //         considered part of the inline storage once they are moved.
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr(), data.data().as_ptr(), len);
+            (*data.header().as_ptr()).len = len;
+
+            self.set_data(data);
+        }
+    }
+
+    /// Leak the vector, returning a slice of its elements that lives for as long as
+    /// the allocator.
+    ///
+    /// If this is the only handle to the elements, the allocation is never freed. If
+    /// there are other handles, only this handle's share is leaked, which keeps the
+    /// whole allocation alive even once the other handles are dropped. Elements that
+    /// are stored inline are moved to the heap first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are stored inline, and moving them to the heap fails.
+    #[must_use]
+    #[track_caller]
+    pub fn leak<'a>(mut self) -> &'a [T]
+    where
+        A: 'a,
+    {
+        if self.is_empty() {
+            return &[];
+        }
+
+        if T::IS_ZST {
+            let len = self.len();
+
+            // Zero-sized elements don't have to live anywhere, so they don't have to be
+            // moved to the heap first.
+            mem::forget(self);
+
+            // SAFETY: Any aligned pointer is valid for zero-sized elements, and the
+            //         elements are never dropped.
+            return unsafe { slice::from_raw_parts(NonNull::dangling().as_ptr(), len) };
+        }
+
+        self.move_inline_to_heap();
+
+        let this = ManuallyDrop::new(self);
+        let view = this.view();
+
+        // SAFETY: The handle is never dropped, so the elements are never mutated or
+        //         freed again.
+        unsafe { slice::from_raw_parts(view.ptr.as_ptr(), view.len) }
+    }
+
+    /// Create an iterator that moves the elements out of the vector in arrays of `N`
+    /// elements, starting at the front.
+    ///
+    /// If the length is not a multiple of `N`, the last elements are left out, see
+    /// [`IntoChunksExact::remainder`]. If this is the only handle to the elements, they
+    /// are moved into the arrays, otherwise they are cloned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn into_chunks_exact<const N: usize>(self) -> IntoChunksExact<T, N, R, A> {
+        assert!(N != 0, "chunk size must be non-zero");
+
+        IntoChunksExact::new(self)
+    }
+
+    /// Turn the vector into an immutable [`SharedSlice`], without copying.
+    ///
+    /// The vector is shrunk to fit first, see [`SharedVec::shrink_to_fit`], so no spare
+    /// capacity is kept if this is the only handle to the elements.
+    #[inline]
+    #[must_use]
+    pub fn freeze(self) -> SharedSlice<T, R, A> {
+        SharedSlice::from(self)
+    }
+
+    /// Reassemble a vector from its raw parts, along with its allocator.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as [`SharedVec::from_raw_parts`] apply, and `alloc` must be
+    ///   the allocator returned along with the parts.
+    #[inline]
+    #[must_use]
+    pub unsafe fn from_raw_parts_in(
+        data: *const (),
+        ptr: *const T,
+        len: usize,
+        alloc: A,
+    ) -> SharedVec<T, R, A> {
+        SharedVec {
+            // SAFETY: The caller ensures that this came from `into_raw_parts_with_alloc`.
+            data: AtomicData::new(unsafe { Data::from_raw(data.cast_mut().cast()) }),
+            storage: Storage {
+                view: View {
+                    // SAFETY: The caller ensures that the view is within the elements,
+                    //         which are never null.
+                    ptr: unsafe { NonNull::new_unchecked(ptr.cast_mut()) },
+                    len,
+                },
+            },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the elements as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements are always initialized, and are never
+        //         mutated while shared.
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len()) }
+    }
+
+    /// Get the elements as an array, if there are exactly `N` of them.
+    #[inline]
+    #[must_use]
+    pub fn as_array<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().try_into().ok()
+    }
+}
+
+impl<T: Clone, R: RefCount> SharedVec<T, R> {
+    /// Create a vector of `len` clones of `elem`, like `vec![elem; len]`.
+    ///
+    /// The clones are written straight into the new allocation, with `elem` itself moved
+    /// into the last slot. See [`SharedVec::from_copy_elem`] for a faster version for
+    /// `Copy` types, which allocates zero bytes as zeroed memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn from_elem(elem: T, len: usize) -> SharedVec<T, R> {
+        let mut vec = SharedVec::with_capacity(len);
+
+        if len == 0 {
+            return vec;
+        }
+
+        // SAFETY: The vector was just created.
+        let mut guard = unsafe { SetLenOnDrop::new(&mut vec) };
+
+        for _ in 1..len {
+            // SAFETY: The vector has room for `len` elements.
+            unsafe { guard.push(elem.clone()) };
+        }
+
+        // SAFETY: The vector has room for the last element.
+        unsafe { guard.push(elem) };
+
+        drop(guard);
+
+        vec
+    }
+
+    /// Create a vector by cloning the elements of `elems`.
+    ///
+    /// See [`SharedVec::from_copy_slice`] for a faster version for `Copy` types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn from_slice(elems: &[T]) -> SharedVec<T, R> {
+        match SharedVec::try_clone_from_slice(elems, elems.len(), Global) {
+            Ok(vec) => vec,
+            Err(error) => handle_reserve_error(error),
+        }
+    }
+
+    /// Turn the vector into a `Vec<T>`, cloning the elements if they are shared.
+    ///
+    /// See [`SharedVec::try_unwrap`] for when the allocation is reused.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        match self.try_unwrap() {
+            Ok(vec) => vec,
+            Err(vec) => vec.as_slice().to_vec(),
+        }
+    }
+
+    /// Turn the vector into a [`Cow`].
+    ///
+    /// Vectors of static data are borrowed, and everything else is turned into an owned
+    /// `Vec<T>` with [`SharedVec::into_vec`].
+    #[must_use]
+    pub fn into_cow<'a>(self) -> Cow<'a, [T]> {
+        if self.data().kind() == Kind::Static {
+            let view = self.view();
+
+            // SAFETY: Static vectors borrow a `&'static [T]`.
+            return Cow::Borrowed(unsafe { slice::from_raw_parts(view.ptr.as_ptr(), view.len) });
+        }
+
+        Cow::Owned(self.into_vec())
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> SharedVec<T, R, A> {
+    /// Try to create a vector with room for `cap` elements that allocates from `alloc`,
+    /// by cloning the elements of `elems`.
+    fn try_clone_from_slice(
+        elems: &[T],
+        cap: usize,
+        alloc: A,
+    ) -> Result<SharedVec<T, R, A>, TryReserveError> {
+        debug_assert!(cap >= elems.len());
+
+        let mut vec: SharedVec<T, R, A> = SharedVec::try_with_capacity_in(cap, alloc)?;
+
+        // SAFETY: The vector was just created.
+        let mut guard = unsafe { SetLenOnDrop::new(&mut vec) };
+
+        for elem in elems {
+            // SAFETY: The vector has room for every element.
+            unsafe { guard.push(elem.clone()) };
+        }
+
+        drop(guard);
+
+        Ok(vec)
+    }
+
+    /// Ensure that this is the only handle to the elements, and that there is room for
+    /// at least `additional` more elements.
+    ///
+    /// If the vector has to grow and `exact` is false, the capacity is at least doubled.
+    #[track_caller]
+    fn reserve_unique(&mut self, additional: usize, exact: bool) {
+        if let Err(error) = self.try_reserve_unique(additional, exact) {
+            handle_reserve_error(error)
+        }
+    }
+
+    /// Try to ensure that this is the only handle to the elements, and that there is room
+    /// for at least `additional` more elements.
+    ///
+    /// If the vector has to grow and `exact` is false, the capacity is at least doubled.
+    fn try_reserve_unique(
+        &mut self,
+        additional: usize,
+        exact: bool,
+    ) -> Result<(), TryReserveError> {
+        if self.is_unique() {
+            if matches!(self.data().kind(), Kind::Heap | Kind::Unique) {
+                // Remember that this handle is unique, so that later mutations don't have
+                // to check the reference count until it is cloned.
+                self.data.set(self.data().with_kind(Kind::Unique));
+
+                // SAFETY: This is the only handle to the heap allocation.
+                unsafe { self.normalize() };
+            }
+
+            // SAFETY: This is the only handle to the elements, and it was just normalized.
+            return unsafe { self.try_grow(additional, exact) };
+        }
+
+        let new_cap = self.grown_capacity(additional, exact)?;
+
+        *self = SharedVec::try_clone_from_slice(self.as_slice(), new_cap, self.alloc.clone())?;
+
+        Ok(())
+    }
+
+    /// Try to reserve room for at least `additional` more elements, cloning the elements
+    /// into a new allocation if there are other handles to them.
+    ///
+    /// On failure the vector is left untouched.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve_unique(additional, false)
+    }
+
+    /// Try to reserve room for exactly `additional` more elements, cloning the elements
+    /// into a new allocation if there are other handles to them.
+    ///
+    /// On failure the vector is left untouched.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve_unique(additional, true)
+    }
+
+    /// Reserve room for at least `additional` more elements, cloning the elements into a
+    /// new allocation if there are other handles to them.
+    ///
+    /// This may reserve more room than requested, to avoid frequent reallocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        self.reserve_unique(additional, false);
+    }
+
+    /// Reserve room for exactly `additional` more elements, cloning the elements into a
+    /// new allocation if there are other handles to them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.reserve_unique(additional, true);
+    }
+
+    /// Create a vector of the elements in `range`.
+    ///
+    /// For vectors on the heap this only bumps the reference count, and the new vector
+    /// shares the allocation. Elements that are stored inline are cloned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    #[must_use]
+    #[track_caller]
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> SharedVec<T, R, A> {
+        let Range { start, end } = range_of(range, self.len());
+
+        if self.data().kind() == Kind::Inline {
+            let elems = &self.as_slice()[start..end];
+
+            return match SharedVec::try_clone_from_slice(elems, elems.len(), self.alloc.clone()) {
+                Ok(vec) => vec,
+                Err(error) => handle_reserve_error(error),
+            };
+        }
+
+        let mut vec = self.clone();
+        let view = vec.view();
+
+        vec.storage.view = View {
+            // SAFETY: `start` is within the view.
+            ptr: unsafe { view.ptr.add(start) },
+            len: end - start,
+        };
+
+        vec
+    }
+
+    /// Create a vector of the elements in `range`, or `None` if it is out of bounds.
+    ///
+    /// This is the non-panicking version of [`SharedVec::slice`], so for vectors on the
+    /// heap the new vector shares the allocation. Unlike a borrowed subslice, the result
+    /// can outlive this handle.
+    #[must_use]
+    pub fn get_shared(&self, range: impl RangeBounds<usize>) -> Option<SharedVec<T, R, A>> {
+        let range = checked_range_of(range, self.len()).ok()?;
+
+        Some(self.slice(range))
+    }
+
+    /// Create a vector of the elements in `sub`, which must be a subslice of this vector,
+    /// like one returned by a parser that borrowed its elements.
+    ///
+    /// This is the same as [`SharedVec::slice`] with the range that `sub` covers, so for
+    /// vectors on the heap the new vector shares the allocation. An empty `sub` always
+    /// returns an empty vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sub` is not within the elements of this vector.
+    #[must_use]
+    #[track_caller]
+    pub fn slice_ref(&self, sub: &[T]) -> SharedVec<T, R, A> {
+        if sub.is_empty() {
+            return SharedVec::new_in(self.alloc.clone());
+        }
+
+        // Zero-sized elements have no address, so any subslice that fits is at the start.
+        let start = if T::IS_ZST {
+            0
+        } else {
+            let addr = strict::addr(self.as_ptr().cast_mut());
+            let sub_addr = strict::addr(sub.as_ptr().cast_mut());
+
+            if sub_addr < addr {
+                panic!("subslice is not within the vector");
+            }
+
+            // `usize::is_multiple_of` needs Rust 1.87, see the `sptr` feature.
+            #[allow(clippy::manual_is_multiple_of)]
+            let misaligned = (sub_addr - addr) % T::SIZE != 0;
+
+            if misaligned {
+                panic!("subslice is not within the vector");
+            }
+
+            (sub_addr - addr) / T::SIZE
+        };
+
+        if sub.len() > self.len().saturating_sub(start) {
+            panic!("subslice is not within the vector");
+        }
+
+        self.slice(start..start + sub.len())
+    }
+
+    /// Split the vector into two at `mid`.
+    ///
+    /// For vectors on the heap both halves share the allocation, see [`SharedVec::slice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    #[must_use]
+    #[track_caller]
+    pub fn split_at(&self, mid: usize) -> (SharedVec<T, R, A>, SharedVec<T, R, A>) {
+        let len = self.len();
+
+        if mid > len {
+            panic!("mid > len");
+        }
+
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Split the vector into two at `at`, returning the elements from `at` onwards and
+    /// leaving the elements before it in `self`.
+    ///
+    /// For vectors on the heap both halves share the allocation, see [`SharedVec::slice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    #[must_use]
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> SharedVec<T, R, A> {
+        let len = self.len();
+
+        if at > len {
+            panic!("`at` split index (is {at}) should be <= len (is {len})");
+        }
+
+        if self.data().kind() == Kind::Inline {
+            let mut tail = SharedVec::with_capacity_in(len - at, self.alloc.clone());
+
+            // SAFETY: The tail fits inline as well, and the elements are no longer
+            //         considered part of `self` once they have been moved.
+            unsafe {
+                ptr::copy_nonoverlapping(self.as_ptr().add(at), tail.as_mut_ptr(), len - at);
+                tail.set_len(len - at);
+
+                self.set_len(at);
+            }
+
+            return tail;
+        }
+
+        let tail = self.slice(at..);
+
+        // Shrinking the view only hides elements from this handle.
+        self.storage.view.len = at;
+
+        tail
+    }
+
+    /// Get an iterator over chunks of `chunk_size` elements, where each chunk is a
+    /// handle to the same elements.
+    ///
+    /// For vectors on the heap the chunks share the allocation, so they can be sent to
+    /// other threads or stored without copying, see [`SharedVec::slice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    #[inline]
+    #[track_caller]
+    pub fn chunks_shared(&self, chunk_size: usize) -> ChunksShared<'_, T, R, A> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+
+        ChunksShared::new(self, chunk_size)
+    }
+
+    /// Get an iterator over the parts of the vector that are separated by elements for
+    /// which `pred` returns `true`, where each part is a handle to the same elements.
+    ///
+    /// For vectors on the heap the parts share the allocation, see
+    /// [`SharedVec::slice`].
+    #[inline]
+    pub fn split_shared<P: FnMut(&T) -> bool>(&self, pred: P) -> SplitShared<'_, T, P, R, A> {
+        SplitShared::new(self, pred)
+    }
+
+    /// Get a mutable slice of the elements, cloning them into a new allocation if
+    /// there are other handles to them.
+    #[must_use]
+    pub fn make_mut(&mut self) -> &mut [T] {
+        self.reserve_unique(0, false);
+
+        // SAFETY: This is the only handle to the elements, and it is borrowed mutably.
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len()) }
+    }
+
+    /// Get a guard that acts like a [`UniqueVec`] of the elements, cloning them into a new
+    /// allocation if there are other handles to them.
+    ///
+    /// This is like [`SharedVec::make_mut`], but also allows changing the length, so a
+    /// series of pushes, pops and so on only has to make the elements unique once. The
+    /// changes are written back to this handle when the guard is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are shared and the new capacity would overflow
+    /// `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn make_mut_vec(&mut self) -> CowGuard<'_, T, R, A> {
+        CowGuard::new(self)
+    }
+
+    /// Get the spare capacity of the vector as a slice of possibly uninitialized
+    /// elements, cloning the elements into a new allocation if there are other handles
+    /// to them.
+    ///
+    /// Once some of the spare capacity has been initialized, [`SharedVec::set_len`] can
+    /// be used to make the elements part of the vector.
+    #[must_use]
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        self.reserve_unique(0, false);
+
+        let len = self.len();
+        let spare = self.capacity() - len;
+
+        // SAFETY: This is the only handle to the allocation, and it has room for
+        //         `spare` more elements after the initialized ones.
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr().add(len).cast(), spare) }
+    }
+
+    /// Append an element to the end of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn push(&mut self, value: T) {
+        self.reserve_unique(1, false);
+
+        let len = self.len();
+
+        // SAFETY: The vector is unique and has room for another element.
+        unsafe {
+            self.as_mut_ptr().add(len).write(value);
+            self.set_len(len + 1);
+        }
+    }
+
+    /// Try to append an element to the end of the vector.
+    ///
+    /// On failure the vector is left untouched, and `value` is dropped.
+    pub fn try_push(&mut self, value: T) -> Result<(), TryReserveError> {
+        self.try_reserve_unique(1, false)?;
+
+        let len = self.len();
+
+        // SAFETY: The vector is unique and has room for another element.
+        unsafe {
+            self.as_mut_ptr().add(len).write(value);
+            self.set_len(len + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the last element of the vector and return it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.reserve_unique(0, false);
+
+        let len = self.len() - 1;
+
+        // SAFETY: The vector is unique, and the element is no longer considered part
+        //         of it once the length is decremented.
+        unsafe {
+            self.set_len(len);
+
+            Some(self.as_mut_ptr().add(len).read())
+        }
+    }
+
+    /// Insert an element at `index`, shifting every element after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, or if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, element: T) {
+        let len = self.len();
+
+        if index > len {
+            panic!("insertion index (is {index}) should be <= len (is {len})");
+        }
+
+        self.reserve_unique(1, false);
+
+        // SAFETY: The vector is unique, has room for another element, and `index` is
+        //         in bounds.
+        unsafe {
+            let ptr = self.as_mut_ptr().add(index);
+
+            ptr::copy(ptr, ptr.add(1), len - index);
+            ptr.write(element);
+
+            self.set_len(len + 1);
+        }
+    }
+
+    /// Remove and return the element at `index`, shifting every element after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+
+        if index >= len {
+            panic!("removal index (is {index}) should be < len (is {len})");
+        }
+
+        self.reserve_unique(0, false);
+
+        // SAFETY: The vector is unique, and `index` is in bounds.
+        unsafe {
+            let ptr = self.as_mut_ptr().add(index);
+            let element = ptr.read();
+
+            ptr::copy(ptr.add(1), ptr, len - index - 1);
+
+            self.set_len(len - 1);
+
+            element
+        }
+    }
+
+    /// Remove the elements in `range` from the vector, and return an iterator over them.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared. See
+    /// [`Drain`] for what happens to the elements when the iterator is dropped or leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[track_caller]
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T, R, A> {
+        let range = range_of(range, self.len());
+
+        self.reserve_unique(0, false);
+
+        // SAFETY: The vector is unique and normalized, and the range is in bounds.
+        unsafe { Drain::new(self, range) }
+    }
+
+    /// Replace the elements in `range` with the elements of `replace_with`, and return
+    /// an iterator over the removed elements.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared. See
+    /// [`Splice`] for when the elements are replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[track_caller]
+    pub fn splice<I: IntoIterator<Item = T>>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: I,
+    ) -> Splice<'_, I::IntoIter, R, A> {
+        Splice::new(self.drain(range), replace_with.into_iter())
+    }
+
+    /// Remove the elements in `range` for which `pred` returns `true`, and return an
+    /// iterator over them.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared. Elements
+    /// are only visited as the iterator is advanced, see [`ExtractIf`] for what happens
+    /// when it is dropped or leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[track_caller]
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        pred: F,
+    ) -> ExtractIf<'_, T, F, R, A> {
+        let range = range_of(range, self.len());
+
+        self.reserve_unique(0, false);
+
+        // SAFETY: The vector is unique and normalized, and the range is in bounds.
+        unsafe { ExtractIf::new(self, range, pred) }
+    }
+
+    /// Resize the vector to `new_len` elements, filling new slots with clones of `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len();
+
+        if new_len <= len {
+            self.truncate(new_len);
+            return;
+        }
+
+        // Reserve room for the last element as well, so pushing it doesn't reallocate.
+        self.reserve_unique(new_len - len, false);
+
+        self.extend_with(new_len - len - 1, || value.clone());
+        self.push(value);
+    }
+
+    /// Resize the vector to `new_len` elements, filling new slots with the values
+    /// returned by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn resize_with(&mut self, new_len: usize, f: impl FnMut() -> T) {
+        let len = self.len();
+
+        if new_len <= len {
+            self.truncate(new_len);
+        } else {
+            self.extend_with(new_len - len, f);
+        }
+    }
+
+    /// Append `n` elements returned by `f`, reserving room for all of them first.
+    #[track_caller]
+    fn extend_with(&mut self, n: usize, mut f: impl FnMut() -> T) {
+        self.reserve_unique(n, false);
+
+        // SAFETY: The vector is unique and normalized.
+        let mut guard = unsafe { SetLenOnDrop::new(self) };
+
+        for _ in 0..n {
+            // SAFETY: The vector has room for `n` more elements.
+            unsafe { guard.push(f()) };
+        }
+    }
+
+    /// Overwrite every element with a clone of `value`.
+    ///
+    /// If there are other handles to the elements, the clones are written into a new
+    /// allocation instead of cloning the old elements first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are shared and the new capacity would overflow
+    /// `isize::MAX` bytes.
+    #[track_caller]
+    pub fn fill(&mut self, value: T) {
+        if self.is_unique() {
+            self.make_mut().fill(value);
+        } else {
+            self.refill(|| value.clone());
+        }
+    }
+
+    /// Overwrite every element with the values returned by `f`.
+    ///
+    /// If there are other handles to the elements, the values are written into a new
+    /// allocation instead of cloning the old elements first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are shared and the new capacity would overflow
+    /// `isize::MAX` bytes.
+    #[track_caller]
+    pub fn fill_with(&mut self, f: impl FnMut() -> T) {
+        if self.is_unique() {
+            self.make_mut().fill_with(f);
+        } else {
+            self.refill(f);
+        }
+    }
+
+    /// Replace the elements with as many values returned by `f` in a new allocation,
+    /// without touching the old elements.
+    #[track_caller]
+    fn refill(&mut self, f: impl FnMut() -> T) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut vec = SharedVec::with_capacity_in(self.len(), self.alloc.clone());
+        vec.extend_with(self.len(), f);
+
+        *self = vec;
+    }
+
+    /// Move the elements of `other` to the end of the vector, leaving `other` empty.
+    ///
+    /// If the vector is empty, the handles are swapped, so the allocation of `other` is
+    /// taken over without copying anything. Otherwise the elements of `other` are moved
+    /// if it is the only handle to them, and cloned if they are shared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn append(&mut self, other: &mut SharedVec<T, R, A>) {
+        if other.is_empty() {
+            return;
+        }
+
+        if self.is_empty() {
+            mem::swap(self, other);
+            return;
+        }
+
+        if !other.is_unique() {
+            self.extend_from_slice(other.as_slice());
+
+            let empty = SharedVec::new_in(other.alloc.clone());
+            *other = empty;
+            return;
+        }
+
+        let len = other.len();
+
+        self.reserve_unique(len, false);
+        other.reserve_unique(0, false);
+
+        // SAFETY: Both vectors are unique, the vector has room for the elements, and they
+        //         are no longer considered part of `other` once they have been moved.
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(self.len()), len);
+            other.set_len(0);
+
+            self.set_len(self.len() + len);
+        }
+    }
+
+    /// Append clones of the elements of `elems` to the end of the vector.
+    ///
+    /// See [`SharedVec::extend_from_copy_slice`] for a faster version for `Copy` types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn extend_from_slice(&mut self, elems: &[T]) {
+        let mut elems = elems.iter();
+
+        self.extend_with(elems.len(), || elems.next().unwrap().clone());
+    }
+
+    /// Remove consecutive repeated elements, keeping the first of each run.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Remove consecutive elements that map to the same key, keeping the first of each run.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared.
+    pub fn dedup_by_key<K: PartialEq>(&mut self, mut key: impl FnMut(&mut T) -> K) {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Remove consecutive elements for which `same_bucket` returns `true`, keeping the
+    /// first of each run.
+    ///
+    /// `same_bucket` is passed the element in question and the last element that was kept,
+    /// in that order. The elements are cloned into a new allocation first if they are
+    /// shared.
+    pub fn dedup_by(&mut self, mut same_bucket: impl FnMut(&mut T, &mut T) -> bool) {
+        /// Shifts the unprocessed elements back over the removed ones, even if
+        /// `same_bucket` or dropping an element panics.
+        struct FillGapOnDrop<'a, T, R: RefCount, A: Allocator> {
+            vec: &'a mut SharedVec<T, R, A>,
+            read: usize,
+            write: usize,
+            len: usize,
+        }
+
+        impl<T, R: RefCount, A: Allocator> Drop for FillGapOnDrop<'_, T, R, A> {
+            fn drop(&mut self) {
+                // SAFETY: The unprocessed elements are initialized, and the removed ones
+                //         have already been dropped.
+                unsafe {
+                    let ptr = self.vec.as_mut_ptr();
+
+                    ptr::copy(
+                        ptr.add(self.read),
+                        ptr.add(self.write),
+                        self.len - self.read,
+                    );
+
+                    self.vec.set_len(self.write + self.len - self.read);
+                }
+            }
+        }
+
+        let len = self.len();
+
+        if len <= 1 {
+            return;
+        }
+
+        self.reserve_unique(0, false);
+
+        let mut gap = FillGapOnDrop {
+            vec: self,
+            read: 1,
+            write: 1,
+            len,
+        };
+
+        let ptr = gap.vec.as_mut_ptr();
+
+        while gap.read < len {
+            // SAFETY: Both elements are initialized and distinct, since the last kept
+            //         element is always before the one being read.
+            unsafe {
+                let read = ptr.add(gap.read);
+                let prev = ptr.add(gap.write - 1);
+
+                if same_bucket(&mut *read, &mut *prev) {
+                    // The element is counted as processed before it is dropped, so it is
+                    // never dropped twice.
+                    gap.read += 1;
+
+                    ptr::drop_in_place(read);
+                } else {
+                    ptr::copy(read, ptr.add(gap.write), 1);
+
+                    gap.write += 1;
+                    gap.read += 1;
+                }
+            }
+        }
+
+        // Every element has been processed, so dropping the guard only sets the length.
+        drop(gap);
+    }
+
+    /// Sort the elements without preserving the order of equal ones.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared.
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.make_mut().sort_unstable();
+    }
+
+    /// Sort the elements with a comparison function, without preserving the order of
+    /// equal ones.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared.
+    pub fn sort_unstable_by(&mut self, compare: impl FnMut(&T, &T) -> cmp::Ordering) {
+        self.make_mut().sort_unstable_by(compare);
+    }
+
+    /// Sort the elements by a key, without preserving the order of equal ones.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared.
+    pub fn sort_unstable_by_key<K: Ord>(&mut self, key: impl FnMut(&T) -> K) {
+        self.make_mut().sort_unstable_by_key(key);
+    }
+
+    /// Keep only the elements for which `f` returns `true`, in order.
+    ///
+    /// If the elements are shared, only the kept elements are cloned into a new allocation.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        if self.is_unique() {
+            self.retain_mut(|elem| f(elem));
+        } else {
+            let mut vec = SharedVec::new_in(self.alloc.clone());
+
+            vec.extend(self.as_slice().iter().filter(|elem| f(elem)).cloned());
+
+            *self = vec;
+        }
+    }
+
+    /// Keep only the elements for which `f` returns `true`, in order, passing a mutable
+    /// reference to each element.
+    ///
+    /// The elements are cloned into a new allocation first if they are shared.
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        /// Shifts the unprocessed elements back over the removed ones, even if `f` or
+        /// dropping an element panics.
+        struct BackshiftOnDrop<'a, T, R: RefCount, A: Allocator> {
+            vec: &'a mut SharedVec<T, R, A>,
+            processed: usize,
+            deleted: usize,
+            len: usize,
+        }
+
+        impl<T, R: RefCount, A: Allocator> Drop for BackshiftOnDrop<'_, T, R, A> {
+            fn drop(&mut self) {
+                // SAFETY: The unprocessed elements are initialized, and the removed ones
+                //         have already been dropped.
+                unsafe {
+                    if self.deleted > 0 {
+                        let ptr = self.vec.as_mut_ptr();
+
+                        ptr::copy(
+                            ptr.add(self.processed),
+                            ptr.add(self.processed - self.deleted),
+                            self.len - self.processed,
+                        );
+                    }
+
+                    self.vec.set_len(self.len - self.deleted);
+                }
+            }
+        }
+
+        self.reserve_unique(0, false);
+
+        let len = self.len();
+
+        // SAFETY: The vector is unique and normalized, and the guard owns the elements
+        //         until it is dropped.
+        unsafe { self.set_len(0) };
+
+        let mut guard = BackshiftOnDrop {
+            vec: self,
+            processed: 0,
+            deleted: 0,
+            len,
+        };
+
+        let ptr = guard.vec.as_mut_ptr();
+
+        while guard.processed < guard.len {
+            // SAFETY: The element is initialized, and it is only accessed once.
+            let elem = unsafe { &mut *ptr.add(guard.processed) };
+
+            if !f(elem) {
+                guard.processed += 1;
+                guard.deleted += 1;
+
+                // SAFETY: The element is counted as deleted before it is dropped, so it is
+                //         never dropped twice.
+                unsafe { ptr::drop_in_place(elem) };
+            } else {
+                if guard.deleted > 0 {
+                    // SAFETY: The hole is within the vector, and was left by a removed element.
+                    unsafe {
+                        ptr::copy_nonoverlapping(elem, ptr.add(guard.processed - guard.deleted), 1)
+                    };
+                }
+
+                guard.processed += 1;
+            }
+        }
+    }
+}
+
+impl<T: Copy, R: RefCount> SharedVec<T, R> {
+    /// Create a vector by copying the elements of `elems` in one go.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn from_copy_slice(elems: &[T]) -> SharedVec<T, R> {
+        let mut vec = SharedVec::with_capacity(elems.len());
+        vec.extend_from_copy_slice(elems);
+        vec
+    }
+
+    /// Create a vector of `len` copies of `elem` in one go.
+    ///
+    /// Zero bytes are allocated as zeroed memory instead, see [`SharedVec::zeroed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn from_copy_elem(elem: T, len: usize) -> SharedVec<T, R>
+    where
+        T: 'static,
+    {
+        if TypeId::of::<T>() == TypeId::of::<u8>() {
+            // SAFETY: `T` is `u8`.
+            let byte = unsafe { *(&elem as *const T).cast::<u8>() };
+
+            if byte == 0 {
+                let vec = ManuallyDrop::new(SharedVec::<u8, R>::zeroed(len));
+
+                // SAFETY: `T` is `u8`, so the vectors are the same type.
+                return unsafe { ptr::read((&*vec as *const SharedVec<u8, R>).cast()) };
+            }
+        }
+
+        let mut vec: SharedVec<T, R> = SharedVec::with_capacity(len);
+        let ptr = vec.as_mut_ptr();
+
+        // SAFETY: The vector was just created with room for `len` elements. Copying can't
+        //         panic.
+        unsafe {
+            for index in 0..len {
+                ptr.add(index).write(elem);
+            }
+
+            vec.set_len(len);
+        }
+
+        vec
+    }
+}
+
+impl<R: RefCount> SharedVec<u8, R> {
+    /// Create a vector of `len` zero bytes.
+    ///
+    /// Unless the bytes are stored inline, they are allocated as zeroed memory, which
+    /// the allocator can often provide without writing to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn zeroed(len: usize) -> SharedVec<u8, R> {
+        SharedVec::zeroed_in(len, Global)
+    }
+}
+
+impl<R: RefCount, A: Allocator> SharedVec<u8, R, A> {
+    /// Create a vector of `len` zero bytes that allocates from `alloc`, see
+    /// [`SharedVec::zeroed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[must_use]
+    #[track_caller]
+    pub fn zeroed_in(len: usize, alloc: A) -> SharedVec<u8, R, A> {
+        if len <= Self::INLINE_CAP {
+            let mut vec: SharedVec<u8, R, A> = SharedVec::with_capacity_in(len, alloc);
+
+            // SAFETY: The vector was just created with room for `len` bytes.
+            unsafe {
+                vec.as_mut_ptr().write_bytes(0, len);
+                vec.set_len(len);
+            }
+
+            return vec;
+        }
+
+        let data = match Data::try_allocate_zeroed(len, &alloc) {
+            Ok(data) => data,
+            Err(error) => handle_reserve_error(error),
+        };
+
+        // SAFETY: The header was just allocated with room for `len` bytes, which are all
+        //         zero.
+        unsafe { (*data.header().as_ptr()).len = len };
+
+        SharedVec {
+            data: AtomicData::new(data),
+            storage: Storage {
+                view: View {
+                    ptr: data.data(),
+                    len,
+                },
+            },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, R: RefCount, A: Allocator + Clone> SharedVec<T, R, A> {
+    /// Overwrite the elements with copies of the elements of `src` in one go.
+    ///
+    /// If there are other handles to the elements, `src` is copied into a new allocation
+    /// instead of cloning the old elements first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` has a different length than the vector, or if the elements are
+    /// shared and the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn copy_from_slice(&mut self, src: &[T]) {
+        let len = self.len();
+
+        if src.len() != len {
+            panic!(
+                "source slice length ({}) does not match destination slice length ({len})",
+                src.len(),
+            );
+        }
+
+        if self.is_unique() {
+            self.make_mut().copy_from_slice(src);
+        } else if len > 0 {
+            let mut vec = SharedVec::with_capacity_in(len, self.alloc.clone());
+            vec.extend_from_copy_slice(src);
+
+            *self = vec;
+        }
+    }
+
+    /// Append copies of the elements of `elems` to the end of the vector in one go.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn extend_from_copy_slice(&mut self, elems: &[T]) {
+        self.reserve_unique(elems.len(), false);
+
+        let len = self.len();
+
+        // SAFETY: The vector is unique and has room for the elements, which can't overlap
+        //         with its spare capacity. Copying can't panic.
+        unsafe {
+            ptr::copy_nonoverlapping(elems.as_ptr(), self.as_mut_ptr().add(len), elems.len());
+            self.set_len(len + elems.len());
+        }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Drop for SharedVec<T, R, A> {
+    fn drop(&mut self) {
+        match self.data().kind() {
+            Kind::Heap => {
+                if !self.header().ref_count.decrement() {
+                    return;
+                }
+            }
+            // No other handles exist, so there is no need to touch the reference count.
+            Kind::Unique => {}
+            Kind::Inline => {
+                let elems = ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len());
+
+                // SAFETY: The inline elements are owned by this handle.
+                unsafe { ptr::drop_in_place(elems) };
+
+                return;
+            }
+            Kind::Static => return,
+        }
+
+        // SAFETY: This was the last handle, so nothing else can access the elements
+        //         or the header. Every element in the allocation is dropped, not just
+        //         the ones in this handle's view.
+        unsafe {
+            let elems =
+                ptr::slice_from_raw_parts_mut(self.data().data().as_ptr(), self.header().len);
+
+            ptr::drop_in_place(elems);
+
+            self.data().deallocate(&self.alloc);
+        }
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> Clone for SharedVec<T, R, A> {
+    /// Create another handle to the elements.
+    ///
+    /// This only bumps the reference count, unless the elements are stored inline, in
+    /// which case they are cloned. Cloning a vector of static data does nothing.
+    #[inline]
+    fn clone(&self) -> Self {
+        // Clone the allocator first, so that a panic can't leave the count bumped.
+        let alloc = self.alloc.clone();
+        let data = self.data();
+
+        match data.kind() {
+            Kind::Heap => {
+                self.header().ref_count.increment();
+            }
+            Kind::Unique => {
+                self.header().ref_count.increment();
+
+                // This handle is no longer unique, so from now on it has to check the
+                // reference count before mutating.
+                self.data.store(data.with_kind(Kind::Heap));
+            }
+            Kind::Inline => {
+                return match SharedVec::try_clone_from_slice(self.as_slice(), self.len(), alloc) {
+                    Ok(vec) => vec,
+                    Err(error) => handle_reserve_error(error),
+                };
+            }
+            Kind::Static => {}
+        }
+
+        SharedVec {
+            data: AtomicData::new(data.with_kind(match data.kind() {
+                Kind::Unique => Kind::Heap,
+                kind => kind,
+            })),
+            // SAFETY: Heap and static vectors only store a view, which can be copied.
+            storage: unsafe { ptr::read(&self.storage) },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Make this vector a copy of `source`, reusing its allocation where possible.
+    ///
+    /// If this is the only handle to its elements and there is room for the elements of
+    /// `source`, they are cloned into the existing allocation, so the vector stays unique
+    /// and can be mutated again without allocating. Otherwise this is the same as
+    /// assigning a [`clone`](Clone::clone) of `source`.
+    fn clone_from(&mut self, source: &Self) {
+        if !self.is_unique() || self.capacity() < source.len() {
+            *self = source.clone();
+            return;
+        }
+
+        self.truncate(source.len());
+
+        let (init, tail) = source.as_slice().split_at(self.len());
+
+        self.make_mut().clone_from_slice(init);
+        self.extend_from_slice(tail);
+    }
+}
+
+impl<T, R: RefCount, A: Allocator + Default> Default for SharedVec<T, R, A> {
+    #[inline]
+    fn default() -> Self {
+        SharedVec::new_in(A::default())
+    }
+}
+
+impl<T, R: RefCount> From<Vec<T>> for SharedVec<T, R> {
+    /// Move the elements of `vec` into a new vector.
+    ///
+    /// This avoids allocating where it can:
+    ///
+    /// - If the elements fit inline, they are moved into the handle and `vec`'s
+    ///   allocation is freed.
+    /// - If `vec` has enough spare capacity to fit the header in front of the elements,
+    ///   and the header is no more aligned than `T` and a whole number of elements long,
+    ///   the elements are shifted back within `vec`'s allocation and the header is
+    ///   written in front of them. Use [`Vec::reserve`] beforehand to make room for it.
+    /// - Otherwise the elements are moved into a new allocation, and `vec`'s allocation is
+    ///   freed.
+    fn from(vec: Vec<T>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        let len = vec.len();
+
+        if Self::INLINE_CAP == 0 || len > Self::INLINE_CAP {
+            if let Some(cap) = Header::<T, R>::capacity_in_vec(vec.capacity()) {
+                if len <= cap {
+                    // SAFETY: `capacity_in_vec` ensures that the allocation has the same
+                    //         layout as a header with room for `cap` elements, so there is
+                    //         room to shift the elements past the header, and `vec` is
+                    //         forgotten without freeing the allocation.
+                    let data = unsafe {
+                        let header =
+                            NonNull::new_unchecked(vec.as_mut_ptr()).cast::<Header<T, R>>();
+
+                        ptr::copy(
+                            vec.as_ptr(),
+                            header.byte_add(Header::<T, R>::DATA_OFFSET).cast().as_ptr(),
+                            len,
+                        );
+
+                        Data::init(header, cap, len)
+                    };
+
+                    return SharedVec {
+                        data: AtomicData::new(data),
+                        storage: Storage {
+                            view: View {
+                                ptr: data.data(),
+                                len,
+                            },
+                        },
+                        alloc: Global,
+                        _marker: PhantomData,
+                    };
+                }
+            }
+        }
+
+        let mut shared = SharedVec::with_capacity(len);
+
+        // SAFETY: The new allocation has room for `len` elements, and the elements are
+        //         moved out of `vec` before its allocation is freed.
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), shared.as_mut_ptr(), len);
+            shared.set_len(len);
+
+            vec.set_len(0);
+            ManuallyDrop::drop(&mut vec);
+        }
+
+        shared
+    }
+}
+
+impl<T, R: RefCount> From<Box<[T]>> for SharedVec<T, R> {
+    /// Move the elements of a boxed slice into a new vector.
+    ///
+    /// A boxed slice has no spare capacity to fit the header in, so unless the elements
+    /// fit inline they are moved into a new allocation, and the box's allocation is
+    /// freed. Convert from a `Vec<T>` with room for the header to avoid this, see
+    /// [`SharedVec::from`].
+    #[inline]
+    fn from(elems: Box<[T]>) -> Self {
+        SharedVec::from(elems.into_vec())
+    }
+}
+
+impl<T, R: RefCount, const N: usize> From<[T; N]> for SharedVec<T, R> {
+    /// Move the elements of an array into a new vector.
+    fn from(elems: [T; N]) -> Self {
+        let elems = ManuallyDrop::new(elems);
+        let mut vec: SharedVec<T, R> = SharedVec::with_capacity(N);
+
+        // SAFETY: The vector has room for `N` elements, and they are moved out of the
+        //         array, which is forgotten.
+        unsafe {
+            ptr::copy_nonoverlapping(elems.as_ptr(), vec.as_mut_ptr(), N);
+            vec.set_len(N);
+        }
+
+        vec
+    }
+}
+
+impl<T: Clone, R: RefCount> From<&[T]> for SharedVec<T, R> {
+    /// Create a vector by cloning the elements of `elems`, see [`SharedVec::from_slice`].
+    #[inline]
+    fn from(elems: &[T]) -> Self {
+        SharedVec::from_slice(elems)
+    }
+}
+
+impl<T: Clone, R: RefCount, const N: usize> From<&[T; N]> for SharedVec<T, R> {
+    /// Create a vector by cloning the elements of `elems`, see [`SharedVec::from_slice`].
+    #[inline]
+    fn from(elems: &[T; N]) -> Self {
+        SharedVec::from_slice(elems)
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone, const N: usize> TryFrom<SharedVec<T, R, A>>
+    for [T; N]
+{
+    type Error = SharedVec<T, R, A>;
+
+    /// Turn a vector of exactly `N` elements into an array, or return the vector if its
+    /// length doesn't match.
+    ///
+    /// The elements are moved out if this is the only handle to them, and cloned if they
+    /// are shared.
+    fn try_from(mut vec: SharedVec<T, R, A>) -> Result<[T; N], SharedVec<T, R, A>> {
+        if vec.len() != N {
+            return Err(vec);
+        }
+
+        if !vec.is_unique() {
+            let elems = vec.as_slice();
+
+            return Ok(core::array::from_fn(|index| elems[index].clone()));
+        }
+
+        // This doesn't clone, since the vector is unique.
+        vec.reserve_unique(0, true);
+
+        // SAFETY: The vector is unique with `N` elements, which are moved out before its
+        //         length is set to zero.
+        unsafe {
+            let array = vec.as_ptr().cast::<[T; N]>().read();
+            vec.set_len(0);
+
+            Ok(array)
+        }
+    }
+}
+
+impl<T: Clone, R: RefCount> From<Cow<'_, [T]>> for SharedVec<T, R> {
+    /// Create a vector from a [`Cow`].
+    ///
+    /// Borrowed elements are cloned, and owned ones are moved like converting from a
+    /// `Vec<T>` does, reusing its allocation where possible.
+    #[inline]
+    fn from(cow: Cow<'_, [T]>) -> Self {
+        match cow {
+            Cow::Borrowed(elems) => SharedVec::from_slice(elems),
+            Cow::Owned(vec) => SharedVec::from(vec),
+        }
+    }
+}
+
+impl<'a, T: Clone, R: RefCount> From<SharedVec<T, R>> for Cow<'a, [T]> {
+    /// Turn a vector into a [`Cow`], see [`SharedVec::into_cow`].
+    #[inline]
+    fn from(vec: SharedVec<T, R>) -> Self {
+        vec.into_cow()
+    }
+}
+
+impl<T, R: RefCount> FromIterator<T> for SharedVec<T, R> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        UniqueVec::from_iter(iter).into_shared()
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> Extend<T> for SharedVec<T, R, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+
+        // Don't make the vector unique if there is nothing to add.
+        let Some(first) = iter.next() else {
+            return;
+        };
+
+        let (lower, _) = iter.size_hint();
+
+        self.reserve(lower.saturating_add(1));
+
+        let cap = self.capacity();
+
+        // Fill the spare capacity without checking whether the vector is unique for each
+        // element, and only push the ones that don't fit.
+        {
+            // SAFETY: Reserving made the vector unique and normalized.
+            let mut guard = unsafe { SetLenOnDrop::new(self) };
+
+            // SAFETY: The vector has room for at least one more element.
+            unsafe { guard.push(first) };
+
+            while guard.len < cap {
+                let Some(elem) = iter.next() else {
+                    return;
+                };
+
+                // SAFETY: The vector has room for another element.
+                unsafe { guard.push(elem) };
+            }
+        }
+
+        iter.for_each(|elem| self.push(elem));
+    }
+}
+
+impl<'a, T: Copy + 'a, R: RefCount, A: Allocator + Clone> Extend<&'a T> for SharedVec<T, R, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Deref for SharedVec<T, R, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> AsRef<[T]> for SharedVec<T, R, A> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Borrow<[T]> for SharedVec<T, R, A> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+/// Only shared indexing is supported, as the elements may be shared with other
+/// handles. Use [`SharedVec::make_mut`] to index into them mutably.
+impl<T, I: SliceIndex<[T]>, R: RefCount, A: Allocator> Index<I> for SharedVec<T, R, A> {
+    type Output = I::Output;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: I) -> &I::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for SharedVec<T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<T: Hash, R: RefCount, A: Allocator> Hash for SharedVec<T, R, A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<T: Eq, R: RefCount, A: Allocator> Eq for SharedVec<T, R, A> {}
+
+impl<T, R1, R2, A1, A2> PartialOrd<SharedVec<T, R2, A2>> for SharedVec<T, R1, A1>
+where
+    T: PartialOrd,
+    R1: RefCount,
+    R2: RefCount,
+    A1: Allocator,
+    A2: Allocator,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &SharedVec<T, R2, A2>) -> Option<cmp::Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord, R: RefCount, A: Allocator> Ord for SharedVec<T, R, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T, U, R1, R2, A1, A2> PartialEq<SharedVec<U, R2, A2>> for SharedVec<T, R1, A1>
+where
+    T: PartialEq<U>,
+    R1: RefCount,
+    R2: RefCount,
+    A1: Allocator,
+    A2: Allocator,
+{
+    #[inline]
+    fn eq(&self, other: &SharedVec<U, R2, A2>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+macro_rules! impl_eq {
+    ($([$($vars:tt)*] $lhs:ty, $rhs:ty, |$a:ident, $b:ident| $eq:expr;)*) => {
+        $(
+            impl<T, U, R: RefCount, A: Allocator, $($vars)*> PartialEq<$rhs> for $lhs
+            where
+                T: PartialEq<U>,
+            {
+                #[inline]
+                fn eq(&self, other: &$rhs) -> bool {
+                    let ($a, $b) = (self, other);
+                    $eq
+                }
+            }
+        )*
+    };
+}
+
+impl_eq! {
+    [] SharedVec<T, R, A>, [U], |a, b| a.as_slice() == b;
+    [] SharedVec<T, R, A>, &[U], |a, b| a.as_slice() == *b;
+    [] SharedVec<T, R, A>, &mut [U], |a, b| a.as_slice() == &**b;
+    [] SharedVec<T, R, A>, Vec<U>, |a, b| a.as_slice() == b.as_slice();
+    [const N: usize] SharedVec<T, R, A>, [U; N], |a, b| a.as_slice() == b.as_slice();
+    [const N: usize] SharedVec<T, R, A>, &[U; N], |a, b| a.as_slice() == b.as_slice();
+    [] [T], SharedVec<U, R, A>, |a, b| a == b.as_slice();
+    [] &[T], SharedVec<U, R, A>, |a, b| *a == b.as_slice();
+    [] &mut [T], SharedVec<U, R, A>, |a, b| &**a == b.as_slice();
+    [] Vec<T>, SharedVec<U, R, A>, |a, b| a.as_slice() == b.as_slice();
+}
+
+/// Sets the length of a unique vector when dropped, so that the elements written to its
+/// spare capacity are kept even if producing the next one panics.
+struct SetLenOnDrop<'a, T, R: RefCount, A: Allocator> {
+    vec: &'a mut SharedVec<T, R, A>,
+    /// The length, including the elements that were written so far.
+    len: usize,
+}
+
+impl<'a, T, R: RefCount, A: Allocator> SetLenOnDrop<'a, T, R, A> {
+    /// Start writing elements after the ones in `vec`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `vec` is the only handle to its elements, and that
+    ///   the view of a heap vector is normalized, see [`SharedVec::normalize`].
+    #[inline]
+    unsafe fn new(vec: &'a mut SharedVec<T, R, A>) -> Self {
+        SetLenOnDrop {
+            len: vec.len(),
+            vec,
+        }
+    }
+
+    /// Write an element after the ones that were written so far.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the vector has room for another element.
+    #[inline]
+    unsafe fn push(&mut self, elem: T) {
+        // SAFETY: The caller ensures that the slot is within the capacity, and it holds
+        //         no element since it is past the length.
+        unsafe { self.vec.as_mut_ptr().add(self.len).write(elem) };
+
+        self.len += 1;
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Drop for SetLenOnDrop<'_, T, R, A> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: The vector is unique and normalized, and the first `len` elements have
+        //         been written.
+        unsafe { self.vec.set_len(self.len) };
+    }
+}
+
+/// Convert `range` into a range of indices within `len`.
+#[track_caller]
+fn range_of(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    match checked_range_of(range, len) {
+        Ok(range) => range,
+        Err(error) => error.panic(),
+    }
+}
+
+/// Convert `range` into a range of indices within `len`, or return why it is out of
+/// bounds.
+fn checked_range_of(
+    range: impl RangeBounds<usize>,
+    len: usize,
+) -> Result<Range<usize>, RangeError> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).ok_or(RangeError::StartOverflow)?,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).ok_or(RangeError::EndOverflow)?,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end {
+        return Err(RangeError::StartAfterEnd { start, end });
+    }
+
+    if end > len {
+        return Err(RangeError::EndOutOfBounds { end, len });
+    }
+
+    Ok(start..end)
+}
+
+/// Why a range is out of bounds.
+enum RangeError {
+    /// The start is excluded and `usize::MAX`.
+    StartOverflow,
+    /// The end is included and `usize::MAX`.
+    EndOverflow,
+    /// The start is after the end.
+    StartAfterEnd { start: usize, end: usize },
+    /// The end is past the length.
+    EndOutOfBounds { end: usize, len: usize },
+}
+
+impl RangeError {
+    /// Panic with the same message as indexing a slice would.
+    #[cold]
+    #[track_caller]
+    fn panic(self) -> ! {
+        match self {
+            RangeError::StartOverflow => {
+                panic!("attempted to index slice from after maximum usize")
+            }
+            RangeError::EndOverflow => panic!("attempted to index slice up to maximum usize"),
+            RangeError::StartAfterEnd { start, end } => {
+                panic!("slice index starts at {start} but ends at {end}")
+            }
+            RangeError::EndOutOfBounds { end, len } => {
+                panic!("range end index {end} out of range for slice of length {len}")
+            }
+        }
+    }
+}
+
+/// The error returned by the fallible allocation methods of [`SharedVec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TryReserveError {
+    /// The capacity would overflow `isize::MAX` bytes.
+    CapacityOverflow,
+    /// The allocator failed to allocate memory.
+    AllocError {
+        /// The layout of the allocation that failed.
+        layout: Layout,
+    },
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryReserveError::AllocError { .. } => f.write_str("memory allocation failed"),
+        }
+    }
+}
+
+impl core::error::Error for TryReserveError {}
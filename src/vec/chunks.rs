@@ -0,0 +1,215 @@
+use core::{cmp, fmt, iter::FusedIterator, ops::Range};
+
+use crate::{
+    allocator::{Allocator, Global},
+    sync::AtomicUsize,
+};
+
+use super::{RefCount, SharedVec};
+
+/// An iterator over chunks of a [`SharedVec`], where each chunk is a handle to the same
+/// elements.
+///
+/// The last chunk is shorter if the length of the vector is not a multiple of the chunk
+/// size. See [`SharedVec::slice`] for when the elements are shared instead of cloned.
+pub struct ChunksShared<'a, T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    vec: &'a SharedVec<T, R, A>,
+    /// The range of elements that have not been yielded yet.
+    range: Range<usize>,
+    chunk_size: usize,
+}
+
+impl<'a, T, R: RefCount, A: Allocator> ChunksShared<'a, T, R, A> {
+    /// Create an iterator over chunks of `vec`, which must have a size of at least one.
+    #[inline]
+    pub(super) fn new(vec: &'a SharedVec<T, R, A>, chunk_size: usize) -> Self {
+        debug_assert!(chunk_size != 0);
+
+        ChunksShared {
+            range: 0..vec.len(),
+            vec,
+            chunk_size,
+        }
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> Iterator for ChunksShared<'_, T, R, A> {
+    type Item = SharedVec<T, R, A>;
+
+    #[inline]
+    fn next(&mut self) -> Option<SharedVec<T, R, A>> {
+        if self.range.is_empty() {
+            return None;
+        }
+
+        let start = self.range.start;
+        let end = start + cmp::min(self.chunk_size, self.range.len());
+
+        self.range.start = end;
+
+        Some(self.vec.slice(start..end))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.range.len().div_ceil(self.chunk_size);
+
+        (len, Some(len))
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> DoubleEndedIterator
+    for ChunksShared<'_, T, R, A>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<SharedVec<T, R, A>> {
+        if self.range.is_empty() {
+            return None;
+        }
+
+        let end = self.range.end;
+        let start = match self.range.len() % self.chunk_size {
+            0 => end - self.chunk_size,
+            rem => end - rem,
+        };
+
+        self.range.end = start;
+
+        Some(self.vec.slice(start..end))
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> ExactSizeIterator for ChunksShared<'_, T, R, A> {}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> FusedIterator for ChunksShared<'_, T, R, A> {}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for ChunksShared<'_, T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunksShared")
+            .field("remaining", &&self.vec.as_slice()[self.range.clone()])
+            .field("chunk_size", &self.chunk_size)
+            .finish()
+    }
+}
+
+/// An iterator over the parts of a [`SharedVec`] that are separated by elements matching
+/// a predicate, where each part is a handle to the same elements.
+///
+/// The separators are not part of any part. See [`SharedVec::slice`] for when the
+/// elements are shared instead of cloned.
+pub struct SplitShared<'a, T, P, R: RefCount = AtomicUsize, A: Allocator = Global>
+where
+    P: FnMut(&T) -> bool,
+{
+    vec: &'a SharedVec<T, R, A>,
+    /// The range of elements that have not been yielded yet.
+    range: Range<usize>,
+    /// Whether the last part has been yielded.
+    finished: bool,
+    pred: P,
+}
+
+impl<'a, T, P, R: RefCount, A: Allocator> SplitShared<'a, T, P, R, A>
+where
+    P: FnMut(&T) -> bool,
+{
+    /// Create an iterator over the parts of `vec` separated by elements matching `pred`.
+    #[inline]
+    pub(super) fn new(vec: &'a SharedVec<T, R, A>, pred: P) -> Self {
+        SplitShared {
+            range: 0..vec.len(),
+            vec,
+            finished: false,
+            pred,
+        }
+    }
+}
+
+impl<T: Clone, P, R: RefCount, A: Allocator + Clone> Iterator for SplitShared<'_, T, P, R, A>
+where
+    P: FnMut(&T) -> bool,
+{
+    type Item = SharedVec<T, R, A>;
+
+    #[inline]
+    fn next(&mut self) -> Option<SharedVec<T, R, A>> {
+        if self.finished {
+            return None;
+        }
+
+        let Range { start, end } = self.range;
+
+        match self.vec.as_slice()[start..end]
+            .iter()
+            .position(&mut self.pred)
+        {
+            Some(index) => {
+                self.range.start = start + index + 1;
+
+                Some(self.vec.slice(start..start + index))
+            }
+            None => {
+                self.finished = true;
+
+                Some(self.vec.slice(start..end))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished {
+            (0, Some(0))
+        } else {
+            (1, Some(self.range.len() + 1))
+        }
+    }
+}
+
+impl<T: Clone, P, R: RefCount, A: Allocator + Clone> DoubleEndedIterator
+    for SplitShared<'_, T, P, R, A>
+where
+    P: FnMut(&T) -> bool,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<SharedVec<T, R, A>> {
+        if self.finished {
+            return None;
+        }
+
+        let Range { start, end } = self.range;
+
+        match self.vec.as_slice()[start..end]
+            .iter()
+            .rposition(&mut self.pred)
+        {
+            Some(index) => {
+                self.range.end = start + index;
+
+                Some(self.vec.slice(start + index + 1..end))
+            }
+            None => {
+                self.finished = true;
+
+                Some(self.vec.slice(start..end))
+            }
+        }
+    }
+}
+
+impl<T: Clone, P, R: RefCount, A: Allocator + Clone> FusedIterator for SplitShared<'_, T, P, R, A> where
+    P: FnMut(&T) -> bool
+{
+}
+
+impl<T: fmt::Debug, P, R: RefCount, A: Allocator> fmt::Debug for SplitShared<'_, T, P, R, A>
+where
+    P: FnMut(&T) -> bool,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplitShared")
+            .field("remaining", &&self.vec.as_slice()[self.range.clone()])
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
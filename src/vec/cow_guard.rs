@@ -0,0 +1,79 @@
+use core::{
+    fmt,
+    mem::{self, ManuallyDrop},
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    sync::AtomicUsize,
+};
+
+use super::{RefCount, SharedVec, UniqueVec};
+
+/// A guard that gives unique access to the elements of a [`SharedVec`] as a
+/// [`UniqueVec`], created with [`SharedVec::make_mut_vec`].
+///
+/// The elements are cloned once when the guard is created if they are shared, after
+/// which any number of edits can be made without checking the reference count again.
+/// The vector is written back to the handle when the guard is dropped, even if an edit
+/// panics. If the guard is leaked, the handle is left empty.
+pub struct CowGuard<'a, T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    /// The handle the vector is written back to, which is empty in the meantime.
+    vec: &'a mut SharedVec<T, R, A>,
+    /// The vector being edited.
+    unique: ManuallyDrop<UniqueVec<T, R, A>>,
+}
+
+impl<'a, T: Clone, R: RefCount, A: Allocator + Clone> CowGuard<'a, T, R, A> {
+    /// Take the elements out of `vec`, cloning them if they are shared.
+    #[track_caller]
+    pub(super) fn new(vec: &'a mut SharedVec<T, R, A>) -> Self {
+        vec.reserve_unique(0, false);
+
+        let empty = SharedVec::new_in(vec.allocator().clone());
+
+        let unique = match UniqueVec::try_from(mem::replace(vec, empty)) {
+            Ok(unique) => unique,
+            Err(_) => unreachable!("the vector was just made unique"),
+        };
+
+        CowGuard {
+            vec,
+            unique: ManuallyDrop::new(unique),
+        }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Deref for CowGuard<'_, T, R, A> {
+    type Target = UniqueVec<T, R, A>;
+
+    #[inline]
+    fn deref(&self) -> &UniqueVec<T, R, A> {
+        &self.unique
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> DerefMut for CowGuard<'_, T, R, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut UniqueVec<T, R, A> {
+        &mut self.unique
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Drop for CowGuard<'_, T, R, A> {
+    fn drop(&mut self) {
+        // SAFETY: The vector is only taken out here, and never used again.
+        let unique = unsafe { ManuallyDrop::take(&mut self.unique) };
+
+        *self.vec = unique.into_shared();
+    }
+}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for CowGuard<'_, T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CowGuard")
+            .field(&self.unique.as_slice())
+            .finish()
+    }
+}
@@ -0,0 +1,135 @@
+use core::{fmt, iter::FusedIterator, ops::Range, ptr, slice};
+
+use crate::{
+    allocator::{Allocator, Global},
+    sync::AtomicUsize,
+};
+
+use super::{RefCount, SharedVec};
+
+/// An iterator that removes a range of elements from a [`SharedVec`] and yields them
+/// by value.
+///
+/// When the iterator is dropped, the elements that were not yielded are dropped, and
+/// the elements after the range are shifted back to close the gap. If the iterator is
+/// leaked, those elements are leaked too, and the vector only keeps the elements
+/// before the range.
+pub struct Drain<'a, T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    /// The vector, with its length set to the start of the range.
+    pub(super) vec: &'a mut SharedVec<T, R, A>,
+    /// The range of elements that have not been yielded yet.
+    pub(super) range: Range<usize>,
+    /// The index of the first element after the range.
+    pub(super) tail: usize,
+    /// The amount of elements after the range.
+    pub(super) tail_len: usize,
+}
+
+impl<'a, T, R: RefCount, A: Allocator> Drain<'a, T, R, A> {
+    /// Create a drain of `range`, which must be in bounds.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `vec` is the only handle to its elements, and that
+    ///   the view of a heap vector is normalized, see [`SharedVec::normalize`].
+    #[inline]
+    pub(super) unsafe fn new(vec: &'a mut SharedVec<T, R, A>, range: Range<usize>) -> Self {
+        let len = vec.len();
+
+        debug_assert!(range.start <= range.end && range.end <= len);
+
+        // SAFETY: The caller ensures that the vector is unique, and the drained elements
+        //         and the tail are owned by the iterator until it is dropped.
+        unsafe { vec.set_len(range.start) };
+
+        Drain {
+            vec,
+            tail: range.end,
+            tail_len: len - range.end,
+            range,
+        }
+    }
+
+    /// Get the elements that have not been yielded yet as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The elements in the range are initialized and have not been moved out.
+        unsafe { slice::from_raw_parts(self.vec.as_ptr().add(self.range.start), self.range.len()) }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Iterator for Drain<'_, T, R, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let index = self.range.next()?;
+
+        // SAFETY: The iterator owns the element, and it has not been moved out.
+        Some(unsafe { self.vec.as_ptr().add(index).read() })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> DoubleEndedIterator for Drain<'_, T, R, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        let index = self.range.next_back()?;
+
+        // SAFETY: The iterator owns the element, and it has not been moved out.
+        Some(unsafe { self.vec.as_ptr().add(index).read() })
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> ExactSizeIterator for Drain<'_, T, R, A> {}
+
+impl<T, R: RefCount, A: Allocator> FusedIterator for Drain<'_, T, R, A> {}
+
+impl<T, R: RefCount, A: Allocator> Drop for Drain<'_, T, R, A> {
+    fn drop(&mut self) {
+        /// Shifts the tail back even if dropping the remaining elements panics.
+        struct MoveTail<'r, 'a, T, R: RefCount, A: Allocator>(&'r mut Drain<'a, T, R, A>);
+
+        impl<T, R: RefCount, A: Allocator> Drop for MoveTail<'_, '_, T, R, A> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let start = drain.vec.len();
+
+                // SAFETY: The tail is initialized, and the vector has room for it right
+                //         after its current length.
+                unsafe {
+                    let ptr = drain.vec.as_mut_ptr();
+
+                    if drain.tail != start {
+                        ptr::copy(ptr.add(drain.tail), ptr.add(start), drain.tail_len);
+                    }
+
+                    drain.vec.set_len(start + drain.tail_len);
+                }
+            }
+        }
+
+        let remaining = ptr::slice_from_raw_parts_mut(
+            self.vec.as_mut_ptr().wrapping_add(self.range.start),
+            self.range.len(),
+        );
+
+        self.range = self.range.end..self.range.end;
+
+        let _guard = MoveTail(self);
+
+        // SAFETY: The iterator owns the remaining elements, and they are not accessed again.
+        unsafe { ptr::drop_in_place(remaining) };
+    }
+}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for Drain<'_, T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_slice()).finish()
+    }
+}
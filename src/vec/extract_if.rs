@@ -0,0 +1,145 @@
+use core::{fmt, ops::Range, ptr, slice};
+
+use crate::{
+    allocator::{Allocator, Global},
+    sync::AtomicUsize,
+};
+
+use super::{RefCount, SharedVec};
+
+/// An iterator that removes the elements in a range of a [`SharedVec`] for which a
+/// predicate returns `true`, and yields them by value.
+///
+/// When the iterator is dropped, the elements that were not visited yet are kept. If
+/// the iterator is leaked, the vector is left empty and all of its elements are leaked.
+pub struct ExtractIf<'a, T, F, R: RefCount = AtomicUsize, A: Allocator = Global>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    /// The vector, with its length set to zero.
+    vec: &'a mut SharedVec<T, R, A>,
+    /// The index of the next element to visit.
+    index: usize,
+    /// The index after the last element to visit.
+    end: usize,
+    /// The amount of elements that were removed so far.
+    removed: usize,
+    /// The length of the vector before any elements were removed.
+    old_len: usize,
+    /// The predicate that decides whether an element is removed.
+    pred: F,
+}
+
+impl<'a, T, F, R: RefCount, A: Allocator> ExtractIf<'a, T, F, R, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    /// Create an iterator that removes elements in `range`, which must be in bounds.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `vec` is the only handle to its elements, and that
+    ///   the view of a heap vector is normalized, see [`SharedVec::normalize`].
+    #[inline]
+    pub(super) unsafe fn new(
+        vec: &'a mut SharedVec<T, R, A>,
+        range: Range<usize>,
+        pred: F,
+    ) -> Self {
+        let old_len = vec.len();
+
+        debug_assert!(range.start <= range.end && range.end <= old_len);
+
+        // SAFETY: The caller ensures that the vector is unique, and the elements are
+        //         owned by the iterator until it is dropped.
+        unsafe { vec.set_len(0) };
+
+        ExtractIf {
+            vec,
+            index: range.start,
+            end: range.end,
+            removed: 0,
+            old_len,
+            pred,
+        }
+    }
+}
+
+impl<T, F, R: RefCount, A: Allocator> Iterator for ExtractIf<'_, T, F, R, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        // SAFETY: The elements are owned by the iterator. Every element before `index`
+        //         that was kept has been moved `removed` slots back, so the slots
+        //         between them and `index` hold no elements.
+        unsafe {
+            let elems = slice::from_raw_parts_mut(self.vec.as_mut_ptr(), self.old_len);
+
+            while self.index < self.end {
+                let index = self.index;
+                let remove = (self.pred)(&mut elems[index]);
+
+                self.index += 1;
+
+                if remove {
+                    self.removed += 1;
+
+                    return Some(ptr::read(&elems[index]));
+                }
+
+                if self.removed > 0 {
+                    let src: *const T = &elems[index];
+                    let dst: *mut T = &mut elems[index - self.removed];
+
+                    ptr::copy_nonoverlapping(src, dst, 1);
+                }
+            }
+        }
+
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.index))
+    }
+}
+
+impl<T, F, R: RefCount, A: Allocator> Drop for ExtractIf<'_, T, F, R, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        // SAFETY: The elements after `index` are still in place, and are moved back to
+        //         directly follow the kept elements before the length is restored. This
+        //         also runs if the predicate panics, in which case the element it
+        //         panicked on is kept.
+        unsafe {
+            if self.index < self.old_len && self.removed > 0 {
+                let ptr = self.vec.as_mut_ptr();
+                let src = ptr.add(self.index);
+
+                ptr::copy(src, src.sub(self.removed), self.old_len - self.index);
+            }
+
+            self.vec.set_len(self.old_len - self.removed);
+        }
+    }
+}
+
+impl<T: fmt::Debug, F, R: RefCount, A: Allocator> fmt::Debug for ExtractIf<'_, T, F, R, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // SAFETY: The elements that were not visited yet are still in place.
+        let remaining = unsafe {
+            slice::from_raw_parts(self.vec.as_ptr().add(self.index), self.end - self.index)
+        };
+
+        f.debug_tuple("ExtractIf").field(&remaining).finish()
+    }
+}
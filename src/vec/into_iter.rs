@@ -0,0 +1,324 @@
+use core::{array, fmt, iter::FusedIterator, ops::Range, ptr, slice};
+
+use crate::{
+    allocator::{Allocator, Global},
+    raw::Kind,
+    sync::AtomicUsize,
+};
+
+use super::{RefCount, SharedVec};
+
+/// An iterator that moves the elements out of a [`SharedVec`].
+///
+/// If the vector was the only handle to its elements, they are yielded by value,
+/// otherwise they are cloned.
+pub struct IntoIter<T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    /// The vector, with a length of zero if the elements are owned by the iterator.
+    vec: SharedVec<T, R, A>,
+    /// Whether the elements are owned by the iterator, rather than shared.
+    owned: bool,
+    /// The range of elements that have not been yielded yet.
+    range: Range<usize>,
+}
+
+impl<T, R: RefCount, A: Allocator> IntoIter<T, R, A> {
+    /// Get the elements that have not been yielded yet as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The elements in the range are initialized and have not been moved out.
+        unsafe { slice::from_raw_parts(self.vec.as_ptr().add(self.range.start), self.range.len()) }
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator> Iterator for IntoIter<T, R, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let index = self.range.next()?;
+
+        if self.owned {
+            // SAFETY: The iterator owns the elements, and this one has not been moved out.
+            Some(unsafe { self.vec.as_ptr().add(index).read() })
+        } else {
+            Some(self.vec.as_slice()[index].clone())
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator> DoubleEndedIterator for IntoIter<T, R, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        let index = self.range.next_back()?;
+
+        if self.owned {
+            // SAFETY: The iterator owns the elements, and this one has not been moved out.
+            Some(unsafe { self.vec.as_ptr().add(index).read() })
+        } else {
+            Some(self.vec.as_slice()[index].clone())
+        }
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator> ExactSizeIterator for IntoIter<T, R, A> {}
+
+impl<T: Clone, R: RefCount, A: Allocator> FusedIterator for IntoIter<T, R, A> {}
+
+impl<T, R: RefCount, A: Allocator> Drop for IntoIter<T, R, A> {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+
+        let remaining = ptr::slice_from_raw_parts_mut(
+            self.vec.as_mut_ptr().wrapping_add(self.range.start),
+            self.range.len(),
+        );
+
+        self.range = 0..0;
+
+        // SAFETY: The iterator owns the remaining elements, and they are not accessed again.
+        //         If dropping one of them panics, the vector still frees its storage.
+        unsafe { ptr::drop_in_place(remaining) };
+    }
+}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for IntoIter<T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator> IntoIterator for SharedVec<T, R, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, R, A>;
+
+    /// Create an iterator that moves the elements out of the vector.
+    ///
+    /// If this is the only handle to the elements, they are yielded by value and the rest
+    /// of the allocation is dropped, otherwise they are cloned.
+    fn into_iter(self) -> IntoIter<T, R, A> {
+        let (vec, owned, len) = take_elements(self);
+
+        IntoIter {
+            vec,
+            owned,
+            range: 0..len,
+        }
+    }
+}
+
+impl<'a, T, R: RefCount, A: Allocator> IntoIterator for &'a SharedVec<T, R, A> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.as_slice().iter()
+    }
+}
+
+/// An iterator that moves the elements out of a [`SharedVec`] in arrays of `N` elements.
+///
+/// If the length of the vector is not a multiple of `N`, the last elements are not
+/// yielded, see [`IntoChunksExact::remainder`]. If the vector was the only handle to its
+/// elements, they are yielded by value, otherwise they are cloned.
+pub struct IntoChunksExact<T, const N: usize, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    /// The vector, with a length of zero if the elements are owned by the iterator.
+    vec: SharedVec<T, R, A>,
+    /// Whether the elements are owned by the iterator, rather than shared.
+    owned: bool,
+    /// The range of elements in chunks that have not been yielded yet.
+    range: Range<usize>,
+    /// The range of elements that don't fill a whole chunk.
+    remainder: Range<usize>,
+}
+
+impl<T, const N: usize, R: RefCount, A: Allocator> IntoChunksExact<T, N, R, A> {
+    /// Create an iterator that moves the elements out of `vec` in arrays of `N` elements,
+    /// which must be at least one.
+    #[inline]
+    pub(super) fn new(vec: SharedVec<T, R, A>) -> Self {
+        debug_assert!(N != 0);
+
+        let (vec, owned, len) = take_elements(vec);
+        let end = len - len % N;
+
+        IntoChunksExact {
+            vec,
+            owned,
+            range: 0..end,
+            remainder: end..len,
+        }
+    }
+
+    /// Get the elements that don't fill a whole chunk, which are never yielded.
+    #[inline]
+    #[must_use]
+    pub fn remainder(&self) -> &[T] {
+        // SAFETY: The remaining elements are initialized and are never moved out.
+        unsafe {
+            slice::from_raw_parts(
+                self.vec.as_ptr().add(self.remainder.start),
+                self.remainder.len(),
+            )
+        }
+    }
+
+    /// Read the chunk that starts at `start`.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that the chunk is within the elements, and that it is not
+    ///   read again.
+    #[inline]
+    unsafe fn read_chunk(&self, start: usize) -> [T; N]
+    where
+        T: Clone,
+    {
+        if self.owned {
+            // SAFETY: The iterator owns the elements, and the caller ensures that these
+            //         have not been moved out.
+            unsafe { self.vec.as_ptr().add(start).cast::<[T; N]>().read() }
+        } else {
+            let elems = &self.vec.as_slice()[start..start + N];
+
+            array::from_fn(|index| elems[index].clone())
+        }
+    }
+}
+
+impl<T: Clone, const N: usize, R: RefCount, A: Allocator> Iterator for IntoChunksExact<T, N, R, A> {
+    type Item = [T; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<[T; N]> {
+        if self.range.is_empty() {
+            return None;
+        }
+
+        let start = self.range.start;
+        self.range.start += N;
+
+        // SAFETY: The range only covers whole chunks, and this one is no longer in it.
+        Some(unsafe { self.read_chunk(start) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.range.len() / N;
+
+        (len, Some(len))
+    }
+}
+
+impl<T: Clone, const N: usize, R: RefCount, A: Allocator> DoubleEndedIterator
+    for IntoChunksExact<T, N, R, A>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<[T; N]> {
+        if self.range.is_empty() {
+            return None;
+        }
+
+        self.range.end -= N;
+
+        // SAFETY: The range only covers whole chunks, and this one is no longer in it.
+        Some(unsafe { self.read_chunk(self.range.end) })
+    }
+}
+
+impl<T: Clone, const N: usize, R: RefCount, A: Allocator> ExactSizeIterator
+    for IntoChunksExact<T, N, R, A>
+{
+}
+
+impl<T: Clone, const N: usize, R: RefCount, A: Allocator> FusedIterator
+    for IntoChunksExact<T, N, R, A>
+{
+}
+
+impl<T, const N: usize, R: RefCount, A: Allocator> Drop for IntoChunksExact<T, N, R, A> {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+
+        let ptr = self.vec.as_mut_ptr();
+        let chunks =
+            ptr::slice_from_raw_parts_mut(ptr.wrapping_add(self.range.start), self.range.len());
+        let remainder = ptr::slice_from_raw_parts_mut(
+            ptr.wrapping_add(self.remainder.start),
+            self.remainder.len(),
+        );
+
+        self.range = 0..0;
+        self.remainder = 0..0;
+
+        /// Drops the remainder even if dropping one of the chunks panics.
+        struct DropRemainder<T>(*mut [T]);
+
+        impl<T> Drop for DropRemainder<T> {
+            fn drop(&mut self) {
+                // SAFETY: The iterator owns the remainder, and it is not accessed again.
+                unsafe { ptr::drop_in_place(self.0) };
+            }
+        }
+
+        let _guard = DropRemainder(remainder);
+
+        // SAFETY: The iterator owns the remaining chunks, and they are not accessed again.
+        //         If dropping one of them panics, the vector still frees its storage.
+        unsafe { ptr::drop_in_place(chunks) };
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, R: RefCount, A: Allocator> fmt::Debug
+    for IntoChunksExact<T, N, R, A>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // SAFETY: The elements in the range are initialized and have not been moved out.
+        let chunks = unsafe {
+            slice::from_raw_parts(self.vec.as_ptr().add(self.range.start), self.range.len())
+        };
+
+        f.debug_struct("IntoChunksExact")
+            .field("remaining", &chunks)
+            .field("remainder", &self.remainder())
+            .finish()
+    }
+}
+
+/// Take the elements out of `vec` if it is the only handle to them, returning the vector,
+/// whether the elements are now owned by the caller, and the amount of elements.
+///
+/// The vector is left with a length of zero if the elements are owned by the caller, who
+/// must drop the ones they don't move out.
+fn take_elements<T, R: RefCount, A: Allocator>(
+    mut vec: SharedVec<T, R, A>,
+) -> (SharedVec<T, R, A>, bool, usize) {
+    let owned = vec.is_unique();
+
+    if owned && matches!(vec.data().kind(), Kind::Heap | Kind::Unique) {
+        vec.data.set(vec.data().with_kind(Kind::Unique));
+
+        // SAFETY: This is the only handle to the heap allocation.
+        unsafe { vec.normalize() };
+    }
+
+    let len = vec.len();
+
+    if owned {
+        // SAFETY: This is the only handle to the elements, and the caller takes ownership
+        //         of them.
+        unsafe { vec.set_len(0) };
+    }
+
+    (vec, owned, len)
+}
@@ -0,0 +1,153 @@
+use core::{cell::Cell, sync::atomic::Ordering};
+
+use crate::sync::{fence, AtomicUsize};
+
+/// The highest count allowed before aborting.
+const MAX_COUNT: usize = isize::MAX as usize;
+
+/// The reference count stored in the header of a [`SharedVec`].
+///
+/// This is implemented for [`AtomicUsize`], which allows handles to be shared between
+/// threads, and for [`Cell<usize>`], which avoids atomic operations entirely for
+/// handles that stay on one thread.
+///
+/// # Safety
+///
+/// - [`RefCount::decrement`] must only return `true` once the count reaches zero, and
+///   [`RefCount::is_one`] must only return `true` when the count is one.
+/// - When either of them returns `true`, every access made through other handles before
+///   they decremented the count must happen before it returns.
+/// - [`RefCount::increment`] must never let the count wrap around, so leaking handles
+///   can't cause a use after free.
+///
+/// [`SharedVec`]: super::SharedVec
+pub unsafe trait RefCount {
+    /// Create a count of one.
+    #[must_use]
+    fn one() -> Self;
+
+    /// Increment the count, aborting if it would overflow.
+    fn increment(&self);
+
+    /// Decrement the count, returning whether it reached zero.
+    #[must_use]
+    fn decrement(&self) -> bool;
+
+    /// Returns whether the count is one.
+    #[must_use]
+    fn is_one(&self) -> bool;
+
+    /// Get the count.
+    ///
+    /// This doesn't synchronize with anything, so the count may be outdated by the time
+    /// it is used.
+    #[must_use]
+    fn count(&self) -> usize;
+}
+
+// SAFETY: Decrements release, and observing the last one acquires.
+unsafe impl RefCount for AtomicUsize {
+    #[inline]
+    fn one() -> Self {
+        AtomicUsize::new(1)
+    }
+
+    #[inline]
+    fn increment(&self) {
+        // Like `Arc`, check after the fact, since the count can only get this high by
+        // leaking handles, and other threads can't push it all the way to `usize::MAX`
+        // before one of them aborts.
+        if self.fetch_add(1, Ordering::Relaxed) > MAX_COUNT {
+            overflow();
+        }
+    }
+
+    #[inline]
+    fn decrement(&self) -> bool {
+        if self.fetch_sub(1, Ordering::Release) != 1 {
+            return false;
+        }
+
+        // Synchronize with the decrements of every other handle, so that their
+        // accesses happen before the caller's.
+        fence(Ordering::Acquire);
+
+        true
+    }
+
+    #[inline]
+    fn is_one(&self) -> bool {
+        self.load(Ordering::Acquire) == 1
+    }
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+// SAFETY: `Cell` is never shared between threads, so every access is ordered already.
+unsafe impl RefCount for Cell<usize> {
+    #[inline]
+    fn one() -> Self {
+        Cell::new(1)
+    }
+
+    #[inline]
+    fn increment(&self) {
+        let count = self.get();
+
+        if count >= MAX_COUNT {
+            overflow();
+        }
+
+        self.set(count + 1);
+    }
+
+    #[inline]
+    fn decrement(&self) -> bool {
+        let count = self.get() - 1;
+
+        self.set(count);
+
+        count == 0
+    }
+
+    #[inline]
+    fn is_one(&self) -> bool {
+        self.get() == 1
+    }
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.get()
+    }
+}
+
+/// Abort on a reference count overflow.
+///
+/// Unwinding isn't enough, since the leaked handles could still be dropped later.
+#[cold]
+#[inline(never)]
+fn overflow() -> ! {
+    #[cfg(feature = "std")]
+    {
+        std::process::abort()
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        /// Panicking while unwinding from another panic aborts.
+        struct Abort;
+
+        impl Drop for Abort {
+            fn drop(&mut self) {
+                panic!("reference count overflow");
+            }
+        }
+
+        let _abort = Abort;
+
+        panic!("reference count overflow");
+    }
+}
@@ -0,0 +1,213 @@
+use core::{fmt, iter::FusedIterator, ptr};
+
+use alloc::vec::Vec;
+
+use crate::{
+    allocator::{Allocator, Global},
+    sync::AtomicUsize,
+};
+
+use super::{Drain, RefCount};
+
+/// An iterator that replaces a range of elements in a [`SharedVec`] with the elements
+/// of another iterator, and yields the removed elements by value.
+///
+/// The replacement happens when the iterator is dropped, whether or not the removed
+/// elements were yielded. If the iterator is leaked, the vector only keeps the elements
+/// before the range, like with [`Drain`].
+///
+/// [`SharedVec`]: super::SharedVec
+pub struct Splice<'a, I, R = AtomicUsize, A = Global>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+    drain: Drain<'a, I::Item, R, A>,
+    replace_with: I,
+}
+
+impl<'a, I, R, A> Splice<'a, I, R, A>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+    /// Create a splice that replaces the elements of `drain` with `replace_with`.
+    #[inline]
+    pub(super) fn new(drain: Drain<'a, I::Item, R, A>, replace_with: I) -> Self {
+        Splice {
+            drain,
+            replace_with,
+        }
+    }
+}
+
+impl<I, R, A> Iterator for Splice<'_, I, R, A>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.drain.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+impl<I, R, A> DoubleEndedIterator for Splice<'_, I, R, A>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<I::Item> {
+        self.drain.next_back()
+    }
+}
+
+impl<I, R, A> ExactSizeIterator for Splice<'_, I, R, A>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+}
+
+impl<I, R, A> FusedIterator for Splice<'_, I, R, A>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+}
+
+impl<I, R, A> Drop for Splice<'_, I, R, A>
+where
+    I: Iterator,
+    I::Item: Clone,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+    fn drop(&mut self) {
+        self.drain.by_ref().for_each(drop);
+
+        if self.drain.tail_len == 0 {
+            self.drain.vec.extend(self.replace_with.by_ref());
+            return;
+        }
+
+        if !self.drain.fill(&mut self.replace_with) {
+            return;
+        }
+
+        // Make room for as many elements as the iterator promises at once, and collect
+        // whatever is left after that so the tail is moved at most twice.
+        let (lower, _) = self.replace_with.size_hint();
+
+        if lower > 0 {
+            self.drain.move_tail(lower);
+
+            if !self.drain.fill(&mut self.replace_with) {
+                return;
+            }
+        }
+
+        let mut rest = self
+            .replace_with
+            .by_ref()
+            .collect::<Vec<I::Item>>()
+            .into_iter();
+
+        if rest.len() > 0 {
+            self.drain.move_tail(rest.len());
+
+            let filled = self.drain.fill(&mut rest);
+
+            debug_assert!(filled && rest.len() == 0);
+        }
+    }
+}
+
+impl<I, R, A> fmt::Debug for Splice<'_, I, R, A>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: Clone + fmt::Debug,
+    R: RefCount,
+    A: Allocator + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Splice")
+            .field("drain", &self.drain)
+            .field("replace_with", &self.replace_with)
+            .finish()
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> Drain<'_, T, R, A> {
+    /// Fill the gap between the vector and the tail with elements of `iter`, returning
+    /// whether the whole gap was filled.
+    fn fill(&mut self, iter: &mut impl Iterator<Item = T>) -> bool {
+        while self.vec.len() < self.tail {
+            let Some(elem) = iter.next() else {
+                return false;
+            };
+
+            let len = self.vec.len();
+
+            // SAFETY: The slot is in the gap, so it is within the capacity and holds no
+            //         element. The length is bumped right after it is written.
+            unsafe {
+                self.vec.as_mut_ptr().add(len).write(elem);
+                self.vec.set_len(len + 1);
+            }
+        }
+
+        true
+    }
+
+    /// Move the tail back to make a gap of `additional` elements before it, which
+    /// requires the current gap to be filled.
+    fn move_tail(&mut self, additional: usize) {
+        let len = self.vec.len();
+        let tail_len = self.tail_len;
+
+        debug_assert_eq!(len, self.tail);
+
+        // Make the tail part of the vector while it grows, so that it is moved along
+        // with the other elements, and still dropped if growing panics.
+        self.tail_len = 0;
+
+        // SAFETY: The gap is filled, so the tail directly follows the other elements.
+        unsafe { self.vec.set_len(len + tail_len) };
+
+        self.vec.reserve_unique(additional, false);
+
+        // SAFETY: The vector has room for `additional` more elements, and the tail is
+        //         owned by the drain again once the length is reset.
+        unsafe {
+            let ptr = self.vec.as_mut_ptr();
+
+            ptr::copy(ptr.add(len), ptr.add(len + additional), tail_len);
+
+            self.vec.set_len(len);
+        }
+
+        self.tail = len + additional;
+        self.tail_len = tail_len;
+    }
+}
@@ -0,0 +1,589 @@
+use alloc::vec::Vec;
+use core::{
+    borrow::{Borrow, BorrowMut},
+    fmt,
+    hash::{Hash, Hasher},
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    ptr, slice,
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    raw::{handle_reserve_error, Kind},
+    slice::SharedSlice,
+    sync::AtomicUsize,
+};
+
+use super::{RefCount, SetLenOnDrop, SharedVec, TryReserveError};
+
+/// A vector that is guaranteed to be the only handle to its elements, for building a
+/// [`SharedVec`] before sharing it.
+///
+/// It is stored the same way as a [`SharedVec`] that was never cloned, so converting it
+/// into one with [`UniqueVec::into_shared`] is free. Since there are no other handles,
+/// mutating it never touches the reference count, and works for elements that can't be
+/// cloned.
+pub struct UniqueVec<T, R: RefCount = AtomicUsize, A: Allocator = Global> {
+    /// The vector, which is unique, inline, or empty and static.
+    vec: SharedVec<T, R, A>,
+}
+
+// SAFETY: The elements and the header are owned by the vector, so it is only shared
+//         or sent along with them.
+unsafe impl<T: Send, R: RefCount, A: Allocator + Send> Send for UniqueVec<T, R, A> {}
+
+// SAFETY: Shared references only give access to the elements and the allocator.
+unsafe impl<T: Sync, R: RefCount, A: Allocator + Sync> Sync for UniqueVec<T, R, A> {}
+
+impl<T, R: RefCount> UniqueVec<T, R> {
+    /// Create an empty vector, without allocating.
+    #[inline]
+    #[must_use]
+    pub fn new() -> UniqueVec<T, R> {
+        UniqueVec::new_in(Global)
+    }
+
+    /// Create an empty vector with room for at least `cap` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn with_capacity(cap: usize) -> UniqueVec<T, R> {
+        UniqueVec::with_capacity_in(cap, Global)
+    }
+
+    /// Try to create an empty vector with room for at least `cap` elements.
+    #[inline]
+    pub fn try_with_capacity(cap: usize) -> Result<UniqueVec<T, R>, TryReserveError> {
+        UniqueVec::try_with_capacity_in(cap, Global)
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> UniqueVec<T, R, A> {
+    /// Create an empty vector that allocates from `alloc`, without allocating.
+    #[inline]
+    #[must_use]
+    pub fn new_in(alloc: A) -> UniqueVec<T, R, A> {
+        UniqueVec {
+            vec: SharedVec::new_in(alloc),
+        }
+    }
+
+    /// Create an empty vector with room for at least `cap` elements, that allocates
+    /// from `alloc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> UniqueVec<T, R, A> {
+        match UniqueVec::try_with_capacity_in(cap, alloc) {
+            Ok(vec) => vec,
+            Err(error) => handle_reserve_error(error),
+        }
+    }
+
+    /// Try to create an empty vector with room for at least `cap` elements, that
+    /// allocates from `alloc`.
+    #[inline]
+    pub fn try_with_capacity_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<UniqueVec<T, R, A>, TryReserveError> {
+        Ok(UniqueVec {
+            vec: SharedVec::try_with_capacity_in(cap, alloc)?,
+        })
+    }
+
+    /// Get the allocator the vector allocates from.
+    #[inline]
+    #[must_use]
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
+    /// Get the amount of elements in the vector.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Get the amount of elements the vector has room for.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Returns whether the vector has no elements.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Get the elements as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        self.vec.as_slice()
+    }
+
+    /// Get the elements as a mutable slice.
+    #[inline]
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        let len = self.len();
+
+        // SAFETY: This is the only handle to the elements, and it is borrowed mutably.
+        unsafe { slice::from_raw_parts_mut(self.vec.as_mut_ptr(), len) }
+    }
+
+    /// Get the elements as an array, if there are exactly `N` of them.
+    #[inline]
+    #[must_use]
+    pub fn as_array<const N: usize>(&self) -> Option<&[T; N]> {
+        self.vec.as_array()
+    }
+
+    /// Get the elements as a mutable array, if there are exactly `N` of them.
+    #[inline]
+    #[must_use]
+    pub fn as_mut_array<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+        self.as_mut_slice().try_into().ok()
+    }
+
+    /// Get the spare capacity of the vector as a slice of possibly uninitialized
+    /// elements, see [`SharedVec::spare_capacity_mut`].
+    #[inline]
+    #[must_use]
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let len = self.len();
+        let spare = self.capacity() - len;
+
+        // SAFETY: This is the only handle to the allocation, and it has room for
+        //         `spare` more elements after the initialized ones.
+        unsafe { slice::from_raw_parts_mut(self.vec.as_mut_ptr().add(len).cast(), spare) }
+    }
+
+    /// Set the length of the vector, without dropping or initializing any elements.
+    ///
+    /// # Safety
+    ///
+    /// - The caller must ensure that `len` is at most [`UniqueVec::capacity`], and that
+    ///   the first `len` elements are initialized.
+    #[inline]
+    pub unsafe fn set_len(&mut self, len: usize) {
+        if self.vec.data().kind() == Kind::Static {
+            // An empty static vector has no room, so the length can only stay zero.
+            debug_assert_eq!(len, 0);
+            return;
+        }
+
+        // SAFETY: The vector is unique and normalized, and the caller ensures that the
+        //         elements are initialized.
+        unsafe { self.vec.set_len(len) };
+    }
+
+    /// Try to reserve room for at least `additional` more elements.
+    ///
+    /// On failure the vector is left untouched.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        // SAFETY: The vector is unique and normalized, and static vectors are empty.
+        unsafe { self.vec.try_grow(additional, false) }
+    }
+
+    /// Try to reserve room for exactly `additional` more elements.
+    ///
+    /// On failure the vector is left untouched.
+    #[inline]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        // SAFETY: The vector is unique and normalized, and static vectors are empty.
+        unsafe { self.vec.try_grow(additional, true) }
+    }
+
+    /// Reserve room for at least `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[track_caller]
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(error) = self.try_reserve(additional) {
+            handle_reserve_error(error)
+        }
+    }
+
+    /// Reserve room for exactly `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[track_caller]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        if let Err(error) = self.try_reserve_exact(additional) {
+            handle_reserve_error(error)
+        }
+    }
+
+    /// Shrink the capacity of the vector to its length, see [`SharedVec::shrink_to_fit`].
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit();
+    }
+
+    /// Append an element to the end of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[inline]
+    #[track_caller]
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+
+        let len = self.len();
+
+        // SAFETY: The vector has room for another element.
+        unsafe {
+            self.vec.as_mut_ptr().add(len).write(value);
+            self.set_len(len + 1);
+        }
+    }
+
+    /// Remove the last element of the vector and return it, or `None` if it is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len().checked_sub(1)?;
+
+        // SAFETY: The element is no longer considered part of the vector once the length
+        //         is decremented.
+        unsafe {
+            self.set_len(len);
+
+            Some(self.vec.as_mut_ptr().add(len).read())
+        }
+    }
+
+    /// Insert an element at `index`, shifting every element after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, or if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, element: T) {
+        let len = self.len();
+
+        if index > len {
+            panic!("insertion index (is {index}) should be <= len (is {len})");
+        }
+
+        self.reserve(1);
+
+        // SAFETY: The vector has room for another element, and `index` is in bounds.
+        unsafe {
+            let ptr = self.vec.as_mut_ptr().add(index);
+
+            ptr::copy(ptr, ptr.add(1), len - index);
+            ptr.write(element);
+
+            self.set_len(len + 1);
+        }
+    }
+
+    /// Remove and return the element at `index`, shifting every element after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+
+        if index >= len {
+            panic!("removal index (is {index}) should be < len (is {len})");
+        }
+
+        // SAFETY: `index` is in bounds.
+        unsafe {
+            let ptr = self.vec.as_mut_ptr().add(index);
+            let element = ptr.read();
+
+            ptr::copy(ptr.add(1), ptr, len - index - 1);
+
+            self.set_len(len - 1);
+
+            element
+        }
+    }
+
+    /// Remove and return the element at `index`, replacing it with the last element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    #[track_caller]
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len();
+
+        if index >= len {
+            panic!("swap_remove index (is {index}) should be < len (is {len})");
+        }
+
+        // SAFETY: `index` is in bounds, and the last element is moved into its place
+        //         before the length is decremented.
+        unsafe {
+            let ptr = self.vec.as_mut_ptr();
+            let element = ptr.add(index).read();
+
+            ptr::copy(ptr.add(len - 1), ptr.add(index), 1);
+
+            self.set_len(len - 1);
+
+            element
+        }
+    }
+
+    /// Shorten the vector to `len` elements, dropping the rest.
+    ///
+    /// This does nothing if `len` is at least the current length.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.vec.truncate(len);
+    }
+
+    /// Remove every element from the vector, keeping its capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Turn the vector into a [`SharedVec`], without copying.
+    ///
+    /// The vector remembers that it is unique, so mutating it doesn't touch the reference
+    /// count until it is cloned.
+    #[inline]
+    #[must_use]
+    pub fn into_shared(self) -> SharedVec<T, R, A> {
+        self.vec
+    }
+
+    /// Turn the vector into an immutable [`SharedSlice`], without copying.
+    #[inline]
+    #[must_use]
+    pub fn freeze(self) -> SharedSlice<T, R, A> {
+        self.vec.freeze()
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator> UniqueVec<T, R, A> {
+    /// Append clones of the elements of `elems` to the end of the vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity would overflow `isize::MAX` bytes.
+    #[track_caller]
+    pub fn extend_from_slice(&mut self, elems: &[T]) {
+        if elems.is_empty() {
+            return;
+        }
+
+        self.reserve(elems.len());
+
+        // SAFETY: The vector is unique, and not static since it has room for elements.
+        let mut guard = unsafe { SetLenOnDrop::new(&mut self.vec) };
+
+        for elem in elems {
+            // SAFETY: There is room for every element.
+            unsafe { guard.push(elem.clone()) };
+        }
+    }
+}
+
+impl<T: Clone, R: RefCount, A: Allocator + Clone> Clone for UniqueVec<T, R, A> {
+    /// Clone the elements into a new allocation.
+    fn clone(&self) -> Self {
+        match SharedVec::try_clone_from_slice(self.as_slice(), self.len(), self.vec.alloc.clone()) {
+            Ok(vec) => UniqueVec { vec },
+            Err(error) => handle_reserve_error(error),
+        }
+    }
+}
+
+impl<T, R: RefCount, A: Allocator + Default> Default for UniqueVec<T, R, A> {
+    #[inline]
+    fn default() -> Self {
+        UniqueVec::new_in(A::default())
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> From<UniqueVec<T, R, A>> for SharedVec<T, R, A> {
+    #[inline]
+    fn from(vec: UniqueVec<T, R, A>) -> Self {
+        vec.into_shared()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> TryFrom<SharedVec<T, R, A>> for UniqueVec<T, R, A> {
+    type Error = SharedVec<T, R, A>;
+
+    /// Turn a [`SharedVec`] into a [`UniqueVec`] if it is the only handle to its
+    /// elements, without copying.
+    ///
+    /// Empty vectors of static data are accepted as well, since they don't own anything.
+    /// Elements of a heap vector that are outside of its view are dropped.
+    fn try_from(mut vec: SharedVec<T, R, A>) -> Result<Self, Self::Error> {
+        match vec.data().kind() {
+            Kind::Heap | Kind::Unique if vec.is_unique() => {
+                vec.data.set(vec.data().with_kind(Kind::Unique));
+
+                // SAFETY: This is the only handle to the heap allocation.
+                unsafe { vec.normalize() };
+            }
+            Kind::Inline => {}
+            Kind::Static if vec.is_empty() => {}
+            _ => return Err(vec),
+        }
+
+        Ok(UniqueVec { vec })
+    }
+}
+
+impl<T, R: RefCount> From<Vec<T>> for UniqueVec<T, R> {
+    /// Convert a `Vec<T>` into a vector, reusing its allocation when possible, see
+    /// [`SharedVec::from`].
+    #[inline]
+    fn from(vec: Vec<T>) -> Self {
+        UniqueVec {
+            vec: SharedVec::from(vec),
+        }
+    }
+}
+
+impl<T, R: RefCount> FromIterator<T> for UniqueVec<T, R> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = UniqueVec::new();
+
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Extend<T> for UniqueVec<T, R, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+
+        self.reserve(iter.size_hint().0);
+
+        let cap = self.capacity();
+
+        // Fill the spare capacity first, and only push the elements that don't fit.
+        if self.len() < cap {
+            // SAFETY: The vector is unique, and not static since it has room for elements.
+            let mut guard = unsafe { SetLenOnDrop::new(&mut self.vec) };
+
+            while guard.len < cap {
+                let Some(elem) = iter.next() else {
+                    return;
+                };
+
+                // SAFETY: The vector has room for another element.
+                unsafe { guard.push(elem) };
+            }
+        }
+
+        iter.for_each(|elem| self.push(elem));
+    }
+}
+
+impl<'a, T: Copy + 'a, R: RefCount, A: Allocator> Extend<&'a T> for UniqueVec<T, R, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Deref for UniqueVec<T, R, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> DerefMut for UniqueVec<T, R, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> AsRef<[T]> for UniqueVec<T, R, A> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> AsMut<[T]> for UniqueVec<T, R, A> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> Borrow<[T]> for UniqueVec<T, R, A> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, R: RefCount, A: Allocator> BorrowMut<[T]> for UniqueVec<T, R, A> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: fmt::Debug, R: RefCount, A: Allocator> fmt::Debug for UniqueVec<T, R, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<T: Hash, R: RefCount, A: Allocator> Hash for UniqueVec<T, R, A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<T, U, R1, R2, A1, A2> PartialEq<UniqueVec<U, R2, A2>> for UniqueVec<T, R1, A1>
+where
+    T: PartialEq<U>,
+    R1: RefCount,
+    R2: RefCount,
+    A1: Allocator,
+    A2: Allocator,
+{
+    #[inline]
+    fn eq(&self, other: &UniqueVec<U, R2, A2>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, R: RefCount, A: Allocator> Eq for UniqueVec<T, R, A> {}
@@ -0,0 +1,221 @@
+use std::{
+    cell::Cell,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use chering::vec::{SharedVec, UniqueVec};
+
+thread_local! {
+    /// The amount of `Tracked` values that are alive on this thread.
+    static ALIVE: Cell<usize> = const { Cell::new(0) };
+    /// The amount of clones that succeed before cloning panics.
+    static CLONES_LEFT: Cell<usize> = const { Cell::new(usize::MAX) };
+}
+
+/// A value that counts how many of its kind are alive, and can be made to panic when
+/// it is cloned.
+#[derive(Debug)]
+struct Tracked(usize);
+
+impl Tracked {
+    fn new(value: usize) -> Tracked {
+        ALIVE.set(ALIVE.get() + 1);
+        Tracked(value)
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Tracked {
+        let left = CLONES_LEFT.get();
+
+        if left == 0 {
+            panic!("clone failed");
+        }
+
+        CLONES_LEFT.set(left - 1);
+        Tracked::new(self.0)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        ALIVE.set(ALIVE.get() - 1);
+    }
+}
+
+fn alive() -> usize {
+    ALIVE.get()
+}
+
+/// Run `f`, making every clone after the first `clones` panic, and assert that it panics.
+fn panics_after(clones: usize, f: impl FnOnce()) {
+    CLONES_LEFT.set(clones);
+
+    let result = catch_unwind(AssertUnwindSafe(f));
+
+    CLONES_LEFT.set(usize::MAX);
+
+    assert!(result.is_err(), "expected a panic");
+}
+
+/// An iterator that yields `len` tracked values and then panics.
+fn panicky_iter(len: usize) -> impl Iterator<Item = Tracked> {
+    (0..=len).map(move |i| {
+        if i == len {
+            panic!("iterator failed");
+        }
+
+        Tracked::new(i)
+    })
+}
+
+fn tracked(len: usize) -> Vec<Tracked> {
+    (0..len).map(Tracked::new).collect()
+}
+
+#[test]
+fn extend_keeps_elements_written_before_the_iterator_panics() {
+    for len in [0, 1, 3, 20] {
+        let mut vec: SharedVec<Tracked> = SharedVec::from(tracked(2));
+
+        panics_after(usize::MAX, || vec.extend(panicky_iter(len)));
+
+        assert_eq!(vec.len(), 2 + len);
+        assert!(vec.iter().skip(2).map(|elem| elem.0).eq(0..len));
+        assert_eq!(alive(), 2 + len);
+
+        drop(vec);
+        assert_eq!(alive(), 0);
+    }
+}
+
+#[test]
+fn extend_beyond_the_size_hint_keeps_elements() {
+    let mut vec: SharedVec<Tracked> = SharedVec::new();
+    let iter = panicky_iter(50).filter(|_| true);
+
+    panics_after(usize::MAX, || vec.extend(iter));
+
+    assert_eq!(vec.len(), 50);
+    assert_eq!(alive(), 50);
+
+    drop(vec);
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn collect_drops_elements_when_the_iterator_panics() {
+    for len in [0, 1, 3, 20] {
+        panics_after(usize::MAX, || {
+            let _: SharedVec<Tracked> = panicky_iter(len).collect();
+        });
+
+        assert_eq!(alive(), 0);
+    }
+}
+
+#[test]
+fn from_slice_drops_clones_when_cloning_panics() {
+    let elems = tracked(10);
+
+    for clones in [0, 1, 5, 9] {
+        panics_after(clones, || {
+            let _: SharedVec<Tracked> = SharedVec::from_slice(&elems);
+        });
+
+        assert_eq!(alive(), 10);
+    }
+
+    drop(elems);
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn extend_from_slice_keeps_elements_cloned_before_the_panic() {
+    let elems = tracked(10);
+    let mut vec: SharedVec<Tracked> = SharedVec::from(tracked(2));
+
+    panics_after(4, || vec.extend_from_slice(&elems));
+
+    assert_eq!(vec.len(), 6);
+    assert_eq!(alive(), 16);
+
+    drop((vec, elems));
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn resize_with_keeps_elements_created_before_the_panic() {
+    let mut vec: SharedVec<Tracked> = SharedVec::from(tracked(2));
+    let mut count = 0;
+
+    panics_after(usize::MAX, || {
+        vec.resize_with(10, || {
+            count += 1;
+
+            if count == 5 {
+                panic!("closure failed");
+            }
+
+            Tracked::new(count)
+        })
+    });
+
+    assert_eq!(vec.len(), 6);
+    assert_eq!(alive(), 6);
+
+    drop(vec);
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn resize_keeps_elements_cloned_before_the_panic() {
+    let mut vec: SharedVec<Tracked> = SharedVec::from(tracked(2));
+
+    panics_after(3, || vec.resize(10, Tracked::new(7)));
+
+    assert_eq!(vec.len(), 5);
+    assert_eq!(alive(), 5);
+
+    drop(vec);
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn failed_clone_on_write_leaves_the_shared_elements_untouched() {
+    let vec: SharedVec<Tracked> = SharedVec::from(tracked(10));
+    let mut other = vec.clone();
+
+    panics_after(3, || other.push(Tracked::new(10)));
+
+    assert_eq!(alive(), 10);
+    assert_eq!(vec.len(), 10);
+    assert_eq!(other.len(), 10);
+    assert_eq!(vec.strong_count(), 2);
+
+    other.push(Tracked::new(10));
+
+    assert_eq!(other.len(), 11);
+    assert_eq!(vec.strong_count(), 1);
+
+    drop((vec, other));
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn unique_vec_extend_keeps_elements_written_before_the_panic() {
+    let mut vec: UniqueVec<Tracked> = UniqueVec::new();
+
+    panics_after(usize::MAX, || vec.extend(panicky_iter(7)));
+
+    assert_eq!(vec.len(), 7);
+    assert_eq!(alive(), 7);
+
+    panics_after(2, || vec.extend_from_slice(&tracked(5)));
+
+    assert_eq!(vec.len(), 9);
+    assert_eq!(alive(), 9);
+
+    drop(vec);
+    assert_eq!(alive(), 0);
+}
@@ -0,0 +1,162 @@
+use std::cell::Cell;
+
+use chering::vec::{SharedVec, UniqueVec};
+
+thread_local! {
+    /// The amount of `Counted` values that were dropped on this thread.
+    static DROPPED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A zero-sized value that counts how often it is dropped.
+#[derive(Debug, Clone, PartialEq)]
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.set(DROPPED.get() + 1);
+    }
+}
+
+fn dropped() -> usize {
+    DROPPED.replace(0)
+}
+
+/// A zero-sized value that is more aligned than the inline storage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(align(64))]
+struct Aligned;
+
+#[test]
+fn zero_sized_elements_dont_allocate() {
+    let mut vec: SharedVec<()> = SharedVec::new();
+
+    for _ in 0..1000 {
+        vec.push(());
+    }
+
+    assert_eq!(vec.len(), 1000);
+    assert!(vec.capacity() >= 1000);
+    assert_eq!(vec.allocated_bytes(), 0);
+    assert_eq!(vec.pop(), Some(()));
+    assert_eq!(vec.len(), 999);
+
+    vec.resize(5000, ());
+    vec.truncate(10);
+
+    assert_eq!(vec.len(), 10);
+    assert_eq!(vec.allocated_bytes(), 0);
+    assert_eq!(vec.iter().count(), 10);
+}
+
+#[test]
+fn zero_sized_elements_are_dropped_once() {
+    let mut vec: SharedVec<Counted> = SharedVec::new();
+
+    vec.resize(10, Counted);
+    assert_eq!(dropped(), 0);
+
+    vec.truncate(4);
+    assert_eq!(dropped(), 6);
+
+    let other = vec.clone();
+    drop(vec);
+    assert_eq!(dropped(), 4);
+
+    let drained = other.into_iter().count();
+    assert_eq!(drained, 4);
+    assert_eq!(dropped(), 4);
+}
+
+#[test]
+fn zero_sized_views_drop_the_hidden_elements() {
+    let vec: SharedVec<Counted> = std::iter::repeat_n(Counted, 8).collect();
+    assert_eq!(dropped(), 0);
+
+    let (data, ptr, len) = vec.into_raw_parts();
+
+    // SAFETY: The parts come straight from `into_raw_parts`.
+    let vec: SharedVec<Counted> = unsafe { SharedVec::from_raw_parts(data, ptr, len) };
+    assert_eq!(
+        vec.allocated_bytes(),
+        SharedVec::<Counted>::header_overhead()
+    );
+
+    let mut middle = vec.slice(2..5);
+    assert_eq!(middle.len(), 3);
+    drop(vec);
+    assert_eq!(dropped(), 0);
+
+    middle.push(Counted);
+    assert_eq!(dropped(), 5);
+    assert_eq!(middle.len(), 4);
+
+    drop(middle);
+    assert_eq!(dropped(), 4);
+}
+
+#[test]
+fn zero_sized_mutation() {
+    let mut vec: SharedVec<Counted> = std::iter::repeat_n(Counted, 10).collect();
+
+    assert_eq!(vec.drain(2..5).count(), 3);
+    assert_eq!(vec.len(), 7);
+    assert_eq!(dropped(), 3);
+
+    let mut count = 0;
+    assert_eq!(
+        vec.extract_if(.., |_| {
+            count += 1;
+            count % 2 == 0
+        })
+        .count(),
+        3
+    );
+    assert_eq!(vec.len(), 4);
+    assert_eq!(dropped(), 3);
+
+    vec.retain(|_| false);
+    assert!(vec.is_empty());
+    assert_eq!(dropped(), 4);
+
+    vec.insert(0, Counted);
+    vec.splice(.., [Counted, Counted]);
+    assert_eq!(vec.len(), 2);
+    assert_eq!(dropped(), 1);
+
+    drop(vec);
+    assert_eq!(dropped(), 2);
+}
+
+#[test]
+fn zero_sized_conversions() {
+    let vec: SharedVec<()> = SharedVec::from(vec![(); 100]);
+    assert_eq!(vec.len(), 100);
+    assert_eq!(vec.allocated_bytes(), 0);
+
+    let back = vec.try_unwrap().unwrap();
+    assert_eq!(back.len(), 100);
+
+    let leaked: &'static [()] = SharedVec::<()>::from(back).leak();
+    assert_eq!(leaked.len(), 100);
+
+    let mut unique: UniqueVec<Counted> = UniqueVec::new();
+    unique.extend(std::iter::repeat_n(Counted, 3));
+    assert_eq!(unique.len(), 3);
+
+    let shared = unique.into_shared();
+    assert_eq!(shared.len(), 3);
+    drop(shared);
+    assert_eq!(dropped(), 3);
+}
+
+#[test]
+fn over_aligned_zero_sized_elements_are_aligned() {
+    let mut vec: SharedVec<Aligned> = SharedVec::new();
+
+    vec.extend([Aligned; 5]);
+    vec.shrink_to_fit();
+
+    assert_eq!(vec.len(), 5);
+    assert_eq!(vec.as_slice().as_ptr() as usize % 64, 0);
+    assert!(vec.iter().all(|elem| *elem == Aligned));
+}